@@ -1,12 +1,40 @@
 #![allow(warnings)]
 #![allow(deprecated)]
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 declare_id!("H8RjKyUCMdkSbUmQFCtpcAAfT4E2tjL7LfswjTm38Vz3");
 
+#[cfg(any(test, feature = "merkle"))]
+pub mod merkle;
+
 // Anchor programs always use 8 bits for the discriminator
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
 
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Upper bound for Config.timelock_delay (30 days)
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * SECONDS_PER_DAY;
+
+// Config.paused bits, one per instruction family
+pub const PAUSE_CREATE_INSTANCE: u8 = 1 << 0;
+pub const PAUSE_CLAIM_INSTANCE: u8 = 1 << 1;
+pub const PAUSE_CAPTAINSHIP: u8 = 1 << 2;
+pub const PAUSE_ADDON: u8 = 1 << 3;
+pub const PAUSE_ALL: u8 = PAUSE_CREATE_INSTANCE | PAUSE_CLAIM_INSTANCE | PAUSE_CAPTAINSHIP | PAUSE_ADDON;
+
+// Basis points denominator for Info.protocol_fee_bps
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Portfolio duration range a new club starts with
+pub const DEFAULT_MIN_PORTFOLIO_DAYS: u16 = 1;
+pub const DEFAULT_MAX_PORTFOLIO_DAYS: u16 = 365;
+
 #[program]
 pub mod open_club {
 
@@ -18,64 +46,193 @@ pub mod open_club {
         title: String,
         instance_limit: u16,
         addon_limit: u16,
+        timelock_delay: i64,
     ) -> Result<()> {
         require!(!ctx.accounts.config.initialized, ErrorCode::AlreadyInitialized); 
+        require!(timelock_delay >= 0 && timelock_delay <= MAX_TIMELOCK_DELAY, ErrorCode::LimitExceeded);
         require!(name.len() <= 20, ErrorCode::LimitExceeded); 
         require!(title.len() <= 64, ErrorCode::LimitExceeded); 
 
         let cfg = &mut ctx.accounts.config;
         cfg.bump = ctx.bumps.config;
+        cfg.club = ctx.accounts.club.key();
         cfg.manager = ctx.accounts.manager.key();
+        cfg.pending_manager = None;
+        cfg.council = Vec::new();
+        cfg.threshold = 0;
+        cfg.proposal_ids = 0;
+        cfg.timelock_delay = timelock_delay;
+        cfg.paused = 0;
         cfg.name = name;
         cfg.title = title;
         cfg.initialized = true;
 
         let stt = &mut ctx.accounts.info;
+        stt.bump = ctx.bumps.info;
         stt.instance_ids = 0;
         stt.instance_limit = instance_limit;
-        stt.addon_limit = addon_limit;
-        stt.admin_requests = Vec::new();
+        // One free, permanent package until the manager queues a real table
+        stt.addon_packages = Vec::new();
+        if addon_limit > 0 {
+            stt.addon_packages.push(AddonPackage { slots: addon_limit, price_lamports: 0, price_tokens: 0, validity_days: 0 });
+        }
+        stt.payment_mint = None;
+        stt.captainship_price_lamports = 0;
+        stt.captainship_price_tokens = 0;
+        stt.protocol_fee_bps = 0;
+        stt.min_portfolio_days = DEFAULT_MIN_PORTFOLIO_DAYS;
+        stt.max_portfolio_days = DEFAULT_MAX_PORTFOLIO_DAYS;
+        stt.max_portfolio_lifetime_days = 0;
+        stt.extend_price_lamports = 0;
+        stt.extend_price_tokens = 0;
+        stt.max_consumers = 0;
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.sol = Revenue::default();
+        treasury.tokens = Revenue::default();
+        treasury.bump = ctx.bumps.treasury;
 
         Ok(())
     }
 
-    pub fn set_manager(
-        ctx: Context<SetConfig>, 
-        new_manager: Pubkey
-    ) -> Result<()> {
-        require!(ctx.accounts.config.manager.key() != new_manager.key(), ErrorCode::ExistingValue);
-        is_manager(&ctx.accounts.config, &ctx.accounts.manager)?;
-        ctx.accounts.config.manager = new_manager;
+    // Not timelocked: the circuit breaker has to act in the same slot
+    pub fn set_pause(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, ErrorCode::InvalidValue);
+        let cfg = &ctx.accounts.config;
+
+        // In council mode a single member may pause more, but lifting a pause needs a proposal
+        let only_adds = paused & cfg.paused == cfg.paused;
+        if !(only_adds && is_council(cfg, &ctx.accounts.signer).is_ok()) {
+            let action = ManagerAction::SetPause { paused };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+
+        ctx.accounts.config.paused = paused;
         Ok(())
     }
 
-    pub fn set_instance_limit(
-        ctx: Context<SetInfo>, 
-        instance_limit: u16
-    ) -> Result<()> {
-        is_manager(&ctx.accounts.config, &ctx.accounts.signer)?;
-        ctx.accounts.info.instance_limit = instance_limit;
+    pub fn accept_manager(ctx: Context<AcceptManager>) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        let pending = cfg.pending_manager.ok_or(ErrorCode::NoPendingManager)?;
+        require!(pending == ctx.accounts.new_manager.key(), ErrorCode::NotPendingManager);
+
+        cfg.manager = pending;
+        cfg.pending_manager = None;
         Ok(())
     }
 
-    pub fn set_addon_limit(
-        ctx: Context<SetInfo>, 
-        addon_limit: u16
-    ) -> Result<()> {
-        is_manager(&ctx.accounts.config, &ctx.accounts.signer)?;
-        ctx.accounts.info.addon_limit = addon_limit;
+    pub fn cancel_manager_proposal(ctx: Context<SetConfig>) -> Result<()> {
+        let pending = ctx.accounts.config.pending_manager.ok_or(ErrorCode::NoPendingManager)?;
+        let action = ManagerAction::CancelManager { new_manager: pending };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        ctx.accounts.config.pending_manager = None;
         Ok(())
     }
 
-    pub fn request_admin(ctx: Context<SetInfo>) -> Result<()> {
-        let stt = &mut ctx.accounts.info;
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: ManagerAction) -> Result<()> {
+        is_council(&ctx.accounts.config, &ctx.accounts.proposer)?;
+
+        let cfg = &mut ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.config = cfg.key();
+        proposal.proposal_id = cfg.proposal_ids;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action;
+        proposal.approvals = vec![ctx.accounts.proposer.key()];
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        cfg.proposal_ids = cfg.proposal_ids.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        is_council(&ctx.accounts.config, &ctx.accounts.member)?;
+        let proposal = &mut ctx.accounts.proposal;
+        let member = ctx.accounts.member.key();
+
+        require!(!proposal.executed, ErrorCode::ProposalExecuted);
+        require!(!proposal.approvals.contains(&member), ErrorCode::AlreadyApproved);
+        require!(proposal.approvals.len() < 10, ErrorCode::ListIsFull);
+
+        proposal.approvals.push(member);
+        Ok(())
+    }
+
+    pub fn close_proposal(_ctx: Context<CloseProposal>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn queue_change(ctx: Context<QueueChange>, change: ConfigChange) -> Result<()> {
+        let action = ManagerAction::QueueChange { change: change.clone() };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        validate_change(&ctx.accounts.config, &change)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let pending = &mut ctx.accounts.pending_change;
+        pending.change = change;
+        pending.queued_by = ctx.accounts.signer.key();
+        pending.eta = now.checked_add(ctx.accounts.config.timelock_delay).ok_or(ErrorCode::Overflow)?;
+        pending.bump = ctx.bumps.pending_change;
+        Ok(())
+    }
+
+    // Permissionless once the delay has passed; the change was authorized when queued
+    pub fn execute_change(ctx: Context<ExecuteChange>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= ctx.accounts.pending_change.eta, ErrorCode::TimelockActive);
 
-        if stt.admin_requests.iter().any(|r|*r == ctx.accounts.signer.key()) {
-            return err!(ErrorCode::AlreadyRequested);            
+        let cfg = &mut ctx.accounts.config;
+        let stt = &mut ctx.accounts.info;
+        match ctx.accounts.pending_change.change.clone() {
+            ConfigChange::InstanceLimit { instance_limit } => stt.instance_limit = instance_limit,
+            ConfigChange::AddonPackages { packages } => stt.addon_packages = packages,
+            ConfigChange::PaymentToken { mint } => stt.payment_mint = mint,
+            ConfigChange::CaptainshipPrice { lamports, tokens } => {
+                stt.captainship_price_lamports = lamports;
+                stt.captainship_price_tokens = tokens;
+            }
+            ConfigChange::ProtocolFee { bps } => stt.protocol_fee_bps = bps,
+            ConfigChange::PortfolioDuration { min_days, max_days } => {
+                stt.min_portfolio_days = min_days;
+                stt.max_portfolio_days = max_days;
+            }
+            ConfigChange::PortfolioExtension { max_lifetime_days, price_lamports, price_tokens } => {
+                stt.max_portfolio_lifetime_days = max_lifetime_days;
+                stt.extend_price_lamports = price_lamports;
+                stt.extend_price_tokens = price_tokens;
+            }
+            ConfigChange::MaxConsumers { max } => {
+                stt.max_consumers = max;
+            }
+            ConfigChange::Manager { new_manager } => cfg.pending_manager = Some(new_manager),
+            ConfigChange::Council { members, threshold } => {
+                cfg.council = members;
+                cfg.threshold = threshold;
+            }
+            ConfigChange::TimelockDelay { delay } => cfg.timelock_delay = delay,
         }
-        require!(stt.admin_requests.len() < 100, ErrorCode::ListIsFull);
+        Ok(())
+    }
+
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+        let action = ManagerAction::CancelChange { change: ctx.accounts.pending_change.change.clone() };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        Ok(())
+    }
+
+    pub fn request_admin(ctx: Context<RequestAdmin>, uri: String) -> Result<()> {
+        require!(uri.len() <= 100, ErrorCode::LimitExceeded);
+
+        let req = &mut ctx.accounts.admin_request;
+        req.wallet = ctx.accounts.requester.key();
+        req.uri = uri;
+        req.created_at = Clock::get()?.unix_timestamp;
+        req.bump = ctx.bumps.admin_request;
+        Ok(())
+    }
 
-        stt.admin_requests.push(ctx.accounts.signer.key());
+    pub fn withdraw_admin_request(_ctx: Context<WithdrawAdminRequest>) -> Result<()> {
         Ok(())
     }
 
@@ -83,14 +240,10 @@ pub mod open_club {
         ctx: Context<ApproveAdmin>, 
         wallet: Pubkey
     ) -> Result<()> {
-        is_manager(&ctx.accounts.config, &ctx.accounts.manager)?;
-
-        // Remove pending request if present
-        let stt = &mut ctx.accounts.info;
-        if let Some(pos) = stt.admin_requests.iter().position(|w| *w == wallet) {
-            stt.admin_requests.swap_remove(pos);
-        }
+        let action = ManagerAction::ApproveAdmin { wallet };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.manager, ctx.accounts.proposal.as_mut(), action)?;
 
+        // Pending request (if any) is closed back to the requester by the account constraints
         let admin = &mut ctx.accounts.admin;
         admin.active = true;
         admin.wallet = wallet;
@@ -100,68 +253,178 @@ pub mod open_club {
     }
 
     pub fn reject_admin(ctx: Context<RejectAdmin>, wallet: Pubkey) -> Result<()> {
-        is_manager(&ctx.accounts.config, &ctx.accounts.manager)?;
-
-        let stt = &mut ctx.accounts.info;
-        if let Some(pos) = stt.admin_requests.iter().position(|w| *w == wallet) {
-            stt.admin_requests.swap_remove(pos);
-            return Ok(());
-        }
-        err!(ErrorCode::RequestNotFound)
+        let action = ManagerAction::RejectAdmin { wallet };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.manager, ctx.accounts.proposal.as_mut(), action)?;
+        Ok(())
     }
 
     pub fn set_admin_status(ctx: Context<AlterAdmin>, status: bool) -> Result<()> {
-        is_manager(&ctx.accounts.config, &ctx.accounts.manager)?;
+        let action = ManagerAction::SetAdminStatus { wallet: ctx.accounts.admin.wallet, status };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.manager, ctx.accounts.proposal.as_mut(), action)?;
         ctx.accounts.admin.active = status;
         Ok(())
     }
 
+    // Instances in `instance_list` are kept as-is: their owner still controls them and
+    // portfolios run out on their own expiry. The wallet can be approved again later.
+    pub fn revoke_admin(ctx: Context<RevokeAdmin>) -> Result<()> {
+        let action = ManagerAction::RevokeAdmin { wallet: ctx.accounts.admin.wallet };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.manager, ctx.accounts.proposal.as_mut(), action)?;
+
+        // The admin paid for its list entries; the base rent is closed back to the manager
+        let info = ctx.accounts.admin.to_account_info();
+        let excess = info.lamports().saturating_sub(Rent::get()?.minimum_balance(Admin::space(0)));
+        **info.try_borrow_mut_lamports()? -= excess;
+        **ctx.accounts.wallet.try_borrow_mut_lamports()? += excess;
+        Ok(())
+    }
+
     pub fn claim_captainship(ctx: Context<InitCaptain>) -> Result<()> {
+        not_paused(&ctx.accounts.config, PAUSE_CAPTAINSHIP)?;
+
+        let stt = &ctx.accounts.info;
+        collect_fee(
+            stt,
+            stt.captainship_price_lamports,
+            stt.captainship_price_tokens,
+            RevenueSource::Captainship,
+            &ctx.accounts.payer,
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.token_payment,
+            &ctx.accounts.system_program,
+        )?;
+
         let cap = &mut ctx.accounts.captain;
         cap.wallet = ctx.accounts.payer.key();
         cap.instance_list = Vec::new();
         cap.remaining_limit = ctx.accounts.info.instance_limit;
+        cap.addon_grants = Vec::new();
         cap.addon_count = 0;
         cap.bump = ctx.bumps.captain;
         Ok(())
     }
     
-    pub fn claim_addon(ctx: Context<GetAddOn>) -> Result<()> {
+    pub fn claim_addon(ctx: Context<GetAddOn>, package_index: u8) -> Result<()> {
+        not_paused(&ctx.accounts.config, PAUSE_ADDON)?;
+
+        let stt = &ctx.accounts.info;
+        let package = *stt.addon_packages.get(package_index as usize).ok_or(ErrorCode::NotFound)?;
+        collect_fee(
+            stt,
+            package.price_lamports,
+            package.price_tokens,
+            RevenueSource::Addon,
+            &ctx.accounts.payer,
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.token_payment,
+            &ctx.accounts.system_program,
+        )?;
+
         let cap = &mut ctx.accounts.captain;
-        cap.remaining_limit = cap.remaining_limit.saturating_add(ctx.accounts.info.addon_limit);
+        if package.validity_days == 0 {
+            cap.remaining_limit = cap.remaining_limit.saturating_add(package.slots);
+        } else {
+            let now = Clock::get()?.unix_timestamp;
+            let ttl = (package.validity_days as i64).checked_mul(SECONDS_PER_DAY).ok_or(ErrorCode::Overflow)?;
+            let expires_at = now.checked_add(ttl).ok_or(ErrorCode::Overflow)?;
+
+            cap.prune_grants(now);
+            require!(cap.addon_grants.len() < 5, ErrorCode::ListIsFull);
+            cap.addon_grants.push(AddonGrant { slots: package.slots, expires_at });
+        }
         cap.addon_count = cap.addon_count.saturating_add(1);
         Ok(())
     }
 
+    // Permissionless; creates the treasury's token account for a payment mint
+    pub fn init_treasury_vault(_ctx: Context<InitTreasuryVault>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64, destination: Pubkey) -> Result<()> {
+        let action = ManagerAction::WithdrawTreasury { amount, destination };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+
+        // Only lamports above the rent-exempt minimum can leave the treasury
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let rent_min = Rent::get()?.minimum_balance(treasury.data_len());
+        let available = treasury.lamports().saturating_sub(rent_min);
+        require!(amount <= available, ErrorCode::InsufficientFunds);
+
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.destination_account.try_borrow_mut_lamports()? += amount;
+
+        let sol = &mut ctx.accounts.treasury.sol;
+        sol.withdrawn = sol.withdrawn.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw_treasury_tokens(
+        ctx: Context<WithdrawTreasuryTokens>,
+        amount: u64,
+        destination: Pubkey,
+    ) -> Result<()> {
+        let action = ManagerAction::WithdrawTreasuryTokens { mint: ctx.accounts.mint.key(), amount, destination };
+        is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        require!(ctx.accounts.treasury_vault.amount >= amount, ErrorCode::InsufficientFunds);
+
+        let config_key = ctx.accounts.config.key();
+        let seeds: &[&[u8]] = &[b"treasury", config_key.as_ref(), &[ctx.accounts.treasury.bump]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.treasury_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.destination_account.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let tokens = &mut ctx.accounts.treasury.tokens;
+        tokens.withdrawn = tokens.withdrawn.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
     pub fn create_instance(
         ctx: Context<CreateInstance>,
         instance_type: InstanceType,
         name: String,
         days: i64,
+        entry_price: u64,
+        max_consumers: u32,
+        opens_at: Option<i64>,
     ) -> Result<()> {
+        not_paused(&ctx.accounts.config, PAUSE_CREATE_INSTANCE)?;
+
+        // 0 = unlimited, only allowed when the club has no ceiling
+        let ceiling = ctx.accounts.info.max_consumers;
+        if ceiling > 0 {
+            require!(max_consumers > 0 && max_consumers <= ceiling, ErrorCode::LimitExceeded);
+        }
+
         if let InstanceType::Private | InstanceType::Whitelisted = instance_type {
-            is_manager(&ctx.accounts.config, &ctx.accounts.creator)?;            
+            let action = ManagerAction::CreateInstance {
+                instance_type,
+                name: name.clone(),
+                entry_price,
+                max_consumers,
+                opens_at,
+            };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.creator, ctx.accounts.proposal.as_mut(), action)?;
         }
 
+        // Active admins create for free; captains spend a slot
         if instance_type == InstanceType::Portfolio {
-            let mut ok = false;
-
-            if let Some(_admin) = ctx.accounts.admin.as_ref() {
-                if _admin.active && _admin.wallet == ctx.accounts.creator.key() {ok = true;}
-            }
-            if !ok {
-                if let Some(_cap) = ctx.accounts.captain.as_ref() {
-                    if _cap.wallet == ctx.accounts.creator.key() && _cap.remaining_limit > 0 {
-                        ok = true;
-                    }
-                }
-            }
-            require!(ok, ErrorCode::NotAuthorized);
-
-            if let Some(_cap) = ctx.accounts.captain.as_deref_mut() {
-                if _cap.wallet == ctx.accounts.creator.key() {
-                    _cap.remaining_limit -= 1;
-                }
+            let is_admin = ctx.accounts.admin.as_ref().map_or(false, |_admin| _admin.active);
+            if !is_admin {
+                let now = Clock::get()?.unix_timestamp;
+                let _cap = ctx.accounts.captain.as_deref_mut().ok_or(ErrorCode::NotAuthorized)?;
+                _cap.use_slot(now)?;
             }
         }
 
@@ -172,16 +435,27 @@ pub mod open_club {
         inst.instance_type = instance_type;
         inst.name = name.to_string();
         inst.owner = ctx.accounts.creator.key();
+        inst.pending_owner = None;
+        inst.moderators = Vec::new();
+        inst.entry_price = entry_price;
         inst.instance_id = ctx.accounts.info.instance_ids;
-        inst.consumers = Vec::new();
+        inst.member_count = 0;
+        inst.max_consumers = max_consumers;
+        inst.opens_at = opens_at;
         inst.whitelist = Vec::new();
+        inst.whitelist_root = None;
         inst.bump = ctx.bumps.instance;
 
+        let now = Clock::get()?.unix_timestamp;
+        inst.created_at = now;
+        inst.expires_at = None;
         if inst.instance_type == InstanceType::Portfolio {
-            let now = Clock::get()?.unix_timestamp;
-            let day = 24_i64 * 60 * 60;
-            let ttl = days.checked_mul(day).ok_or(ErrorCode::Overflow)?;
-            inst.expires_at = now.checked_add(ttl).ok_or(ErrorCode::Overflow)?;
+            let stt = &ctx.accounts.info;
+            require!(days >= stt.min_portfolio_days as i64, ErrorCode::InvalidDuration);
+            require!(days <= stt.max_portfolio_days as i64, ErrorCode::InvalidDuration);
+
+            let ttl = days.checked_mul(SECONDS_PER_DAY).ok_or(ErrorCode::Overflow)?;
+            inst.expires_at = Some(now.checked_add(ttl).ok_or(ErrorCode::Overflow)?);
         }
 
         // Track in creator lists
@@ -201,50 +475,398 @@ pub mod open_club {
         Ok(())
     }
 
-    pub fn grant_private_instance(ctx: Context<SetInstance>, wallet: Pubkey) -> Result<()> {
-        is_manager(&ctx.accounts.config, &ctx.accounts.manager)?;
+    pub fn transfer_instance(ctx: Context<OwnerInstance>, new_owner: Pubkey) -> Result<()> {
+        let inst = &mut ctx.accounts.instance;
+        require!(inst.owner == ctx.accounts.owner.key(), ErrorCode::NotAuthorized);
+        require!(inst.owner != new_owner, ErrorCode::ExistingValue);
+        require!(is_owner_run(inst), ErrorCode::NotAuthorized);
+
+        inst.pending_owner = Some(new_owner);
+        Ok(())
+    }
+
+    pub fn cancel_instance_transfer(ctx: Context<OwnerInstance>) -> Result<()> {
+        let inst = &mut ctx.accounts.instance;
+        require!(inst.owner == ctx.accounts.owner.key(), ErrorCode::NotAuthorized);
+        require!(inst.pending_owner.is_some(), ErrorCode::NoPendingOwner);
+
+        inst.pending_owner = None;
+        Ok(())
+    }
+
+    pub fn set_entry_price(ctx: Context<SetInstance>, entry_price: u64) -> Result<()> {
+        if is_owner_run(&ctx.accounts.instance) {
+            require!(ctx.accounts.instance.owner == ctx.accounts.signer.key(), ErrorCode::NotAuthorized);
+        } else {
+            let action = ManagerAction::SetEntryPrice { instance: ctx.accounts.instance.key(), entry_price };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+
+        ctx.accounts.instance.entry_price = entry_price;
+        Ok(())
+    }
+
+    pub fn set_opens_at(ctx: Context<SetInstance>, opens_at: Option<i64>) -> Result<()> {
+        if is_owner_run(&ctx.accounts.instance) {
+            require!(ctx.accounts.instance.owner == ctx.accounts.signer.key(), ErrorCode::NotAuthorized);
+        } else {
+            let action = ManagerAction::SetOpensAt { instance: ctx.accounts.instance.key(), opens_at };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+
+        ctx.accounts.instance.opens_at = opens_at;
+        Ok(())
+    }
+
+    pub fn add_moderator(ctx: Context<SetInstance>, wallet: Pubkey) -> Result<()> {
+        if is_owner_run(&ctx.accounts.instance) {
+            require!(ctx.accounts.instance.owner == ctx.accounts.signer.key(), ErrorCode::NotAuthorized);
+        } else {
+            let action = ManagerAction::AddModerator { instance: ctx.accounts.instance.key(), wallet };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+        let inst = &mut ctx.accounts.instance;
+        require!(inst.moderators.len() < 5, ErrorCode::ListIsFull);
+        require!(!inst.moderators.contains(&wallet), ErrorCode::ExistingValue);
+
+        inst.moderators.push(wallet);
+        Ok(())
+    }
+
+    pub fn remove_moderator(ctx: Context<SetInstance>, wallet: Pubkey) -> Result<()> {
+        if is_owner_run(&ctx.accounts.instance) {
+            require!(ctx.accounts.instance.owner == ctx.accounts.signer.key(), ErrorCode::NotAuthorized);
+        } else {
+            let action = ManagerAction::RemoveModerator { instance: ctx.accounts.instance.key(), wallet };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+        let inst = &mut ctx.accounts.instance;
+
+        let pos = inst.moderators.iter().position(|w| *w == wallet).ok_or(ErrorCode::NotFound)?;
+        inst.moderators.swap_remove(pos);
+        Ok(())
+    }
+
+    pub fn accept_instance(ctx: Context<AcceptInstance>) -> Result<()> {
+        let new_owner = ctx.accounts.new_owner.key();
+        let inst_key = ctx.accounts.instance.key();
+        let inst = &mut ctx.accounts.instance;
+
+        let pending = inst.pending_owner.ok_or(ErrorCode::NoPendingOwner)?;
+        require!(pending == new_owner, ErrorCode::NotPendingOwner);
+
+        // Same capacity rules as create_instance: active admin, or captain spending a slot
+        if inst.instance_type == InstanceType::Portfolio {
+            let is_admin = ctx.accounts.new_admin.as_ref().map_or(false, |_admin| _admin.active);
+            if !is_admin {
+                let now = Clock::get()?.unix_timestamp;
+                let _cap = ctx.accounts.new_captain.as_deref_mut().ok_or(ErrorCode::NotAuthorized)?;
+                _cap.use_slot(now)?;
+            }
+        }
+
+        // Move the key between creator lists
+        let old_owner = ctx.accounts.old_owner.to_account_info();
+        unlist_instance::<Admin>(&ctx.accounts.old_admin, &old_owner, &inst_key)?;
+        unlist_instance::<Captain>(&ctx.accounts.old_captain, &old_owner, &inst_key)?;
+        if let Some(_admin) = ctx.accounts.new_admin.as_deref_mut() {
+            require!(_admin.instance_list.len() < 200, ErrorCode::ListIsFull);
+            _admin.instance_list.push(inst_key);
+        }
+        if let Some(_cap) = ctx.accounts.new_captain.as_deref_mut() {
+            require!(_cap.instance_list.len() < 200, ErrorCode::ListIsFull);
+            _cap.instance_list.push(inst_key);
+        }
+
+        inst.owner = new_owner;
+        inst.pending_owner = None;
+        inst.moderators = Vec::new();   // the new owner picks their own
+        Ok(())
+    }
+
+    // Extends from the current expiry, or from now if the portfolio already lapsed
+    pub fn extend_instance(ctx: Context<ExtendInstance>, days: i64) -> Result<()> {
+        let inst = &ctx.accounts.instance;
+        require!(inst.owner == ctx.accounts.owner.key(), ErrorCode::NotAuthorized);
+        require!(inst.instance_type == InstanceType::Portfolio, ErrorCode::NotAuthorized);
+        require!(days > 0, ErrorCode::InvalidValue);
+
+        let stt = &ctx.accounts.info;
+        let now = Clock::get()?.unix_timestamp;
+        let ttl = days.checked_mul(SECONDS_PER_DAY).ok_or(ErrorCode::Overflow)?;
+        let current = inst.expires_at.ok_or(ErrorCode::InvalidValue)?;
+        let expires_at = current.max(now).checked_add(ttl).ok_or(ErrorCode::Overflow)?;
+
+        if stt.max_portfolio_lifetime_days > 0 {
+            let max_ttl = (stt.max_portfolio_lifetime_days as i64).checked_mul(SECONDS_PER_DAY).ok_or(ErrorCode::Overflow)?;
+            let lifetime = expires_at.checked_sub(inst.created_at).ok_or(ErrorCode::Overflow)?;
+            require!(lifetime <= max_ttl, ErrorCode::LimitExceeded);
+        }
+
+        collect_fee(
+            stt,
+            stt.extend_price_lamports,
+            stt.extend_price_tokens,
+            RevenueSource::InstanceFee,
+            &ctx.accounts.owner,
+            &mut ctx.accounts.treasury,
+            &ctx.accounts.token_payment,
+            &ctx.accounts.system_program,
+        )?;
+
+        ctx.accounts.instance.expires_at = Some(expires_at);
+        Ok(())
+    }
+
+    // Rent goes back to the owner; the name can be reused once the account is gone
+    // Memberships are left behind; they carry the instance_id, so they don't count for a
+    // re-created instance of the same name
+    pub fn close_instance(ctx: Context<CloseInstance>) -> Result<()> {
+        let inst_key = ctx.accounts.instance.key();
+        let is_owner = ctx.accounts.signer.key() == ctx.accounts.instance.owner;
+        if !(is_owner && is_owner_run(&ctx.accounts.instance)) {
+            let action = ManagerAction::CloseInstance { instance: inst_key };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+        let owner = ctx.accounts.owner.to_account_info();
+        unlist_instance::<Admin>(&ctx.accounts.owner_admin, &owner, &inst_key)?;
+        unlist_instance::<Captain>(&ctx.accounts.owner_captain, &owner, &inst_key)?;
+        Ok(())
+    }
+
+    pub fn grant_private_instance(ctx: Context<GrantInstance>, wallet: Pubkey) -> Result<()> {
+        if !is_moderator(&ctx.accounts.instance, &ctx.accounts.signer) {
+            let action = ManagerAction::GrantPrivateInstance { instance: ctx.accounts.instance.key(), wallet };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
         let inst = &mut ctx.accounts.instance;
 
         require!(inst.instance_type == InstanceType::Private, ErrorCode::NotAuthorized);
-        require!(inst.consumers.len() < 200, ErrorCode::LimitExceeded);
-        require!(!inst.consumers.iter().any(|w| *w == wallet), ErrorCode::ExistingValue);
 
-        inst.consumers.push(wallet);
+        let mbr = &mut ctx.accounts.membership;
+        require!(!mbr.is_current(inst), ErrorCode::ExistingValue);
+        require!(inst.has_room(), ErrorCode::InstanceFull);
+
+        // A stale record keeps its payer, who funded the rent
+        if mbr.joined_at == 0 {
+            mbr.payer = ctx.accounts.signer.key();
+        }
+        mbr.instance = inst.key();
+        mbr.instance_id = inst.instance_id;
+        mbr.wallet = wallet;
+        mbr.role = MemberRole::Granted;
+        mbr.joined_at = Clock::get()?.unix_timestamp;
+        mbr.bump = ctx.bumps.membership;
+
+        inst.member_count = inst.member_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
         Ok(())
     }
 
-    pub fn add_whitelist(ctx: Context<SetInstance>, wallet: Pubkey) -> Result<()> {
-        is_manager(&ctx.accounts.config, &ctx.accounts.manager)?;
+    pub fn add_whitelist(ctx: Context<AddWhitelist>, wallet: Pubkey) -> Result<()> {
+        if !is_moderator(&ctx.accounts.instance, &ctx.accounts.signer) {
+            let action = ManagerAction::AddWhitelist { instance: ctx.accounts.instance.key(), wallet };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
         let inst = &mut ctx.accounts.instance;
 
         require!(inst.instance_type == InstanceType::Whitelisted, ErrorCode::NotAuthorized);
         require!(inst.whitelist.len() < 200, ErrorCode::LimitExceeded);
-        require!(!inst.consumers.iter().any(|w| *w == wallet), ErrorCode::ExistingValue);
 
-        inst.whitelist.push(wallet);
+        // Kept sorted so claims can binary search
+        let pos = inst.whitelist.binary_search(&wallet).err().ok_or(ErrorCode::ExistingValue)?;
+        inst.whitelist.insert(pos, wallet);
+        Ok(())
+    }
+
+    pub fn add_whitelist_batch(ctx: Context<AddWhitelistBatch>, wallets: Vec<Pubkey>) -> Result<()> {
+        if !is_moderator(&ctx.accounts.instance, &ctx.accounts.signer) {
+            let action = ManagerAction::AddWhitelistBatch { instance: ctx.accounts.instance.key(), wallets: wallets.clone() };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+        let inst = &mut ctx.accounts.instance;
+
+        require!(inst.instance_type == InstanceType::Whitelisted, ErrorCode::NotAuthorized);
+        require!(wallets.len() <= 20, ErrorCode::LimitExceeded);
+        require!(inst.whitelist.len() + wallets.len() <= 200, ErrorCode::LimitExceeded);
+
+        let mut batch = wallets;
+        batch.sort();
+        require!(batch.windows(2).all(|w| w[0] != w[1]), ErrorCode::ExistingValue);
+        require!(batch.iter().all(|w| inst.whitelist.binary_search(w).is_err()), ErrorCode::ExistingValue);
+
+        inst.whitelist.extend(batch);
+        inst.whitelist.sort();
+        Ok(())
+    }
+
+    pub fn set_whitelist_root(ctx: Context<SetInstance>, root: Option<[u8; 32]>) -> Result<()> {
+        if !is_moderator(&ctx.accounts.instance, &ctx.accounts.signer) {
+            let action = ManagerAction::SetWhitelistRoot { instance: ctx.accounts.instance.key(), root };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+        let inst = &mut ctx.accounts.instance;
+
+        require!(inst.instance_type == InstanceType::Whitelisted, ErrorCode::NotAuthorized);
+        inst.whitelist_root = root;
+        Ok(())
+    }
+
+    pub fn remove_whitelist(ctx: Context<RemoveWhitelist>, wallet: Pubkey) -> Result<()> {
+        if !is_moderator(&ctx.accounts.instance, &ctx.accounts.signer) {
+            let action = ManagerAction::RemoveWhitelist { instance: ctx.accounts.instance.key(), wallet };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+        let inst = &mut ctx.accounts.instance;
+
+        let pos = inst.whitelist.binary_search(&wallet).map_err(|_| ErrorCode::NotFound)?;
+        inst.whitelist.remove(pos);
+        Ok(())
+    }
+
+    // Membership is closed by the accounts constraint. The instance may be gone or
+    // re-created since; only a membership of the live instance counts.
+    pub fn leave_instance(ctx: Context<LeaveInstance>) -> Result<()> {
+        let info = ctx.accounts.instance.to_account_info();
+        let wallet = ctx.accounts.member.key();
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(());
+        }
+
+        let mut inst = Instance::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        if !ctx.accounts.membership.is_current(&inst) {
+            return Ok(());
+        }
+        inst.member_count = inst.member_count.checked_sub(1).ok_or(ErrorCode::Overflow)?;
+        inst.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit!(ConsumerLeft { instance: info.key(), wallet });
+        Ok(())
+    }
+
+    pub fn remove_consumer(ctx: Context<RemoveConsumer>, wallet: Pubkey) -> Result<()> {
+        let inst_key = ctx.accounts.instance.key();
+        let signer = ctx.accounts.signer.key();
+        let is_owner = signer == ctx.accounts.instance.owner && is_owner_run(&ctx.accounts.instance);
+        if !is_owner && !is_moderator(&ctx.accounts.instance, &ctx.accounts.signer) {
+            let action = ManagerAction::RemoveConsumer { instance: inst_key, wallet };
+            is_authorized(&ctx.accounts.config, &ctx.accounts.signer, ctx.accounts.proposal.as_mut(), action)?;
+        }
+        let inst = &mut ctx.accounts.instance;
+
+        // Membership is closed by the accounts constraint; a stale one never counted
+        if !ctx.accounts.membership.is_current(inst) {
+            return Ok(());
+        }
+        inst.member_count = inst.member_count.checked_sub(1).ok_or(ErrorCode::Overflow)?;
+
+        emit!(ConsumerRemoved { instance: inst_key, wallet, removed_by: signer });
         Ok(())
     }
 
-    pub fn claim_instance(ctx: Context<ClaimInstance>) -> Result<()> {
+    pub fn claim_instance(
+        ctx: Context<ClaimInstance>,
+        proof: Vec<[u8; 32]>,
+        invite: Option<Invite>,
+    ) -> Result<()> {
+        not_paused(&ctx.accounts.config, PAUSE_CLAIM_INSTANCE)?;
         let inst = &mut ctx.accounts.instance;
         let claimer_key = ctx.accounts.claimer.key();
-        require!(inst.consumers.len() < 200, ErrorCode::LimitExceeded);
+
+        // The nonce account is only passed with an invite, so it can't be burned without one
+        require!(invite.is_some() == ctx.accounts.used_invite.is_some(), ErrorCode::InvalidInvite);
+        // Existing members have nothing to claim; failing keeps an invite from being spent
+        if ctx.accounts.membership.is_current(inst) {
+            require!(invite.is_none(), ErrorCode::ExistingValue);
+            return Ok(());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(opens_at) = inst.opens_at {
+            require!(now >= opens_at, ErrorCode::NotYetOpen);
+        }
+
+        // Signed invite; its nonce account is created by the accounts constraint, so it works once
+        let invited = match &invite {
+            Some(invite) => {
+                require!(invite.instance == inst.key(), ErrorCode::InvalidInvite);
+                require!(invite.instance_id == inst.instance_id, ErrorCode::InvalidInvite);
+                require!(invite.invitee.map_or(true, |w| w == claimer_key), ErrorCode::InvalidInvite);
+                require!(now <= invite.expires_at, ErrorCode::Expired);
+
+                let inviter = invite_signer(&ctx.accounts.config, inst)?;
+                let ixs = ctx.accounts.instructions.as_ref().ok_or(ErrorCode::InvalidInvite)?;
+                verify_invite(ixs, &inviter, invite)?;
+
+                let used = ctx.accounts.used_invite.as_deref_mut().ok_or(ErrorCode::InvalidInvite)?;
+                used.claimer = claimer_key;
+                used.bump = ctx.bumps.used_invite.ok_or(ErrorCode::InvalidInvite)?;
+                true
+            }
+            None => false,
+        };
 
         match inst.instance_type {
             InstanceType::Public => {}
-            InstanceType::Private => {return err!(ErrorCode::NotManager);}
+            InstanceType::Private => require!(invited, ErrorCode::NotManager),
             InstanceType::Whitelisted => {
-                require!(inst.whitelist.iter().any(|w| *w == claimer_key), ErrorCode::NotAuthorized);
+                // Listed on-chain, proven against the off-chain list's root, or invited
+                let listed = inst.whitelist.binary_search(&claimer_key).is_ok()
+                    || inst.whitelist_root.is_some_and(|root| verify_proof(root, &claimer_key, &proof));
+                require!(listed || invited, ErrorCode::NotAuthorized);
             }
             InstanceType::Portfolio => {
-                let now = Clock::get()?.unix_timestamp;
-                require!(now <= inst.expires_at, ErrorCode::Expired);
+                let expires_at = inst.expires_at.ok_or(ErrorCode::Expired)?;
+                require!(now <= expires_at, ErrorCode::Expired);
             }
         }
-        if inst.consumers.iter().all(|claimer| *claimer != claimer_key) {
-            inst.consumers.push(claimer_key);
+        require!(inst.has_room(), ErrorCode::InstanceFull);
+
+        // Paid entry: protocol fee to the treasury, the rest to the instance owner
+        if inst.entry_price > 0 {
+            let stt = &ctx.accounts.info;
+            let (fee, share) = split_entry_price(inst.entry_price, stt.protocol_fee_bps);
+            collect_fee(
+                stt,
+                fee,
+                fee,
+                RevenueSource::InstanceFee,
+                &ctx.accounts.claimer,
+                &mut ctx.accounts.treasury,
+                &ctx.accounts.token_payment,
+                &ctx.accounts.system_program,
+            )?;
+
+            match stt.payment_mint {
+                Some(mint) => pay_tokens(
+                    mint,
+                    share,
+                    &ctx.accounts.claimer,
+                    ctx.accounts.owner_token_account.as_ref(),
+                    inst.owner,
+                    &ctx.accounts.token_payment,
+                )?,
+                None => pay_lamports(
+                    share,
+                    &ctx.accounts.claimer,
+                    &ctx.accounts.owner.to_account_info(),
+                    &ctx.accounts.system_program,
+                )?,
+            }
         }
 
+        // A stale record keeps its payer, who funded the rent
+        let mbr = &mut ctx.accounts.membership;
+        if mbr.joined_at == 0 {
+            mbr.payer = claimer_key;
+        }
+        mbr.instance = inst.key();
+        mbr.instance_id = inst.instance_id;
+        mbr.wallet = claimer_key;
+        mbr.role = if invited { MemberRole::Invited } else { MemberRole::Member };
+        mbr.joined_at = now;
+        mbr.bump = ctx.bumps.membership;
+
+        inst.member_count = inst.member_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
         Ok(())
     }
 }
@@ -256,7 +878,15 @@ pub mod open_club {
 #[derive(InitSpace)]
 pub struct Config {
     pub initialized: bool,
+    pub club: Pubkey,                      // config seed; every other PDA is seeded by the config key
     pub manager: Pubkey,
+    pub pending_manager: Option<Pubkey>,   // set by an executed Manager change, cleared on accept/cancel
+    #[max_len(10)]
+    pub council: Vec<Pubkey>,
+    pub threshold: u8,                     // 0 = single-key mode, otherwise M-of-N council
+    pub proposal_ids: u64,
+    pub timelock_delay: i64,               // seconds between queue_change and execute_change
+    pub paused: u8,                        // bitmask of PAUSE_* flags
     #[max_len(20)]
     pub name: String,
     #[max_len(64)]
@@ -264,14 +894,31 @@ pub struct Config {
     pub bump: u8,
 }
 
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct AddonPackage {
+    pub slots: u16,
+    pub price_lamports: u64,
+    pub price_tokens: u64,              // in base units of Info.payment_mint
+    pub validity_days: u16,             // 0 = slots never expire
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Info {
     pub instance_ids: u64,
     pub instance_limit: u16,
-    pub addon_limit: u16,
-    #[max_len(100)]
-    pub admin_requests: Vec<Pubkey>,
+    #[max_len(5)]
+    pub addon_packages: Vec<AddonPackage>,
+    pub payment_mint: Option<Pubkey>,   // when set, paid instructions charge this SPL / Token-2022 mint
+    pub captainship_price_lamports: u64,
+    pub captainship_price_tokens: u64,
+    pub protocol_fee_bps: u16,          // treasury share of instance entry prices
+    pub min_portfolio_days: u16,        // accepted range for create_instance(days)
+    pub max_portfolio_days: u16,
+    pub max_portfolio_lifetime_days: u16,   // cap on expires_at - created_at; 0 = no cap
+    pub extend_price_lamports: u64,
+    pub extend_price_tokens: u64,
+    pub max_consumers: u32,             // ceiling for Instance.max_consumers; 0 = no ceiling
     pub bump: u8,
 }
 
@@ -279,12 +926,14 @@ pub struct Info {
 pub struct InitConfig<'info> {
     #[account(mut)]
     pub manager: Signer<'info>,
+    // Any fresh keypair; it only signs once so nobody else can claim the club's address
+    pub club: Signer<'info>,
 
     #[account(
         init,
         payer = manager,
         space = ANCHOR_DISCRIMINATOR + Config::INIT_SPACE,
-        seeds = [b"config"],
+        seeds = [b"config", club.key().as_ref()],
         bump,
     )]
     pub config: Account<'info, Config>,
@@ -293,85 +942,323 @@ pub struct InitConfig<'info> {
         init,
         payer = manager,
         space = ANCHOR_DISCRIMINATOR + Info::INIT_SPACE,
-        seeds = [b"info"],
+        seeds = [b"info", config.key().as_ref()],
         bump,
     )]
     pub info: Account<'info, Info>,
 
+    #[account(
+        init,
+        payer = manager,
+        space = ANCHOR_DISCRIMINATOR + Treasury::INIT_SPACE,
+        seeds = [b"treasury", config.key().as_ref()],
+        bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct SetConfig<'info> {
-    #[account(mut)]
-    pub manager: Signer<'info>,
-    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    // Manager, or council member with a proposal
+    pub signer: Signer<'info>,
+    #[account(mut, seeds = [b"config", config.club.as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
 }
 
 #[derive(Accounts)]
-pub struct SetInfo<'info> {
-    #[account(mut)]
+pub struct SetPause<'info> {
     pub signer: Signer<'info>,
+    #[account(mut, seeds = [b"config", config.club.as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+}
 
-    #[account(mut, seeds = [b"info"], bump)]
-    pub info: Account<'info, Info>,
+#[derive(Accounts)]
+pub struct AcceptManager<'info> {
+    pub new_manager: Signer<'info>,
+    #[account(mut, seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+/* -------------------------------------------------------- */
+/* ----------------------- Timelock ----------------------- */
+/* -------------------------------------------------------- */
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    InstanceLimit { instance_limit: u16 },
+    AddonPackages {
+        #[max_len(5)]
+        packages: Vec<AddonPackage>,
+    },
+    PaymentToken { mint: Option<Pubkey> },   // None = pay in SOL
+    CaptainshipPrice { lamports: u64, tokens: u64 },
+    ProtocolFee { bps: u16 },
+    PortfolioDuration { min_days: u16, max_days: u16 },
+    PortfolioExtension { max_lifetime_days: u16, price_lamports: u64, price_tokens: u64 },
+    MaxConsumers { max: u32 },
+    Manager { new_manager: Pubkey },
+    Council {
+        #[max_len(10)]
+        members: Vec<Pubkey>,
+        threshold: u8,
+    },
+    TimelockDelay { delay: i64 },
+}
+
+impl ConfigChange {
+    // One pending change per kind
+    pub fn seed(&self) -> &'static [u8] {
+        match self {
+            ConfigChange::InstanceLimit { .. } => b"instance_limit",
+            ConfigChange::AddonPackages { .. } => b"addon_packages",
+            ConfigChange::PaymentToken { .. } => b"payment_token",
+            ConfigChange::CaptainshipPrice { .. } => b"captainship_price",
+            ConfigChange::ProtocolFee { .. } => b"protocol_fee",
+            ConfigChange::PortfolioDuration { .. } => b"portfolio_duration",
+            ConfigChange::PortfolioExtension { .. } => b"portfolio_extension",
+            ConfigChange::MaxConsumers { .. } => b"max_consumers",
+            ConfigChange::Manager { .. } => b"manager",
+            ConfigChange::Council { .. } => b"council",
+            ConfigChange::TimelockDelay { .. } => b"timelock_delay",
+        }
+    }
 }
 
-/* ----------------------------------------------------- */
-/* ----------------------- Admin ----------------------- */
-/* ----------------------------------------------------- */
 #[account]
 #[derive(InitSpace)]
-pub struct Admin {
-    pub active: bool,
-    pub wallet: Pubkey,
-    #[max_len(200)]
-    pub instance_list: Vec<Pubkey>,
+pub struct PendingChange {
+    pub change: ConfigChange,
+    pub queued_by: Pubkey,
+    pub eta: i64,               // unix timestamp (seconds)
     pub bump: u8,
 }
 
 #[derive(Accounts)]
-pub struct AlterAdmin<'info> {
-    pub manager: Signer<'info>,
-    #[account(seeds = [b"config"], bump = config.bump)]
-    pub config: Account<'info, Config>,
-    #[account(mut, seeds = [b"admin", admin.wallet.as_ref()], bump = admin.bump)]
-    pub admin: Account<'info, Admin>,
-}
-
-// RequestAdmin
-#[derive(Accounts)]
-#[instruction(wallet: Pubkey)]
-pub struct ApproveAdmin<'info> {
+#[instruction(change: ConfigChange)]
+pub struct QueueChange<'info> {
     #[account(mut)]
-    pub manager: Signer<'info>,
-    #[account(seeds = [b"config"], bump = config.bump)]
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
 
-    #[account(seeds = [b"info"], bump = info.bump)]
-    pub info: Account<'info, Info>,
-
-     #[account( 
-        init, 
-        payer = manager, 
-        space = 8 + Admin::INIT_SPACE,
-        seeds = [b"admin", wallet.as_ref()], 
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + PendingChange::INIT_SPACE,
+        seeds = [b"pending_change", config.key().as_ref(), change.seed()],
         bump,
     )]
-    pub admin: Account<'info, Admin>,
+    pub pending_change: Account<'info, PendingChange>,
+
+    // Required in council mode; validated in is_authorized
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RejectAdmin<'info> {
+pub struct ExecuteChange<'info> {
+    #[account(mut, seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"info", config.key().as_ref()], bump = info.bump)]
+    pub info: Account<'info, Info>,
+
+    #[account(
+        mut,
+        close = queued_by,
+        seeds = [b"pending_change", config.key().as_ref(), pending_change.change.seed()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    #[account(mut, address = pending_change.queued_by)]
+    pub queued_by: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = queued_by,
+        seeds = [b"pending_change", config.key().as_ref(), pending_change.change.seed()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    #[account(mut, address = pending_change.queued_by)]
+    pub queued_by: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+}
+
+/* ----------------------------------------------------- */
+/* ----------------------- Admin ----------------------- */
+/* ----------------------------------------------------- */
+#[account]
+#[derive(InitSpace)]
+pub struct Admin {
+    pub active: bool,
+    pub wallet: Pubkey,
+    #[max_len(200)]
+    pub instance_list: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl Admin {
+    // Sized for `n` listed instances; grows and shrinks with realloc
+    pub fn space(n: usize) -> usize {
+        ANCHOR_DISCRIMINATOR + Admin::INIT_SPACE - (200 - n.min(200)) * 32
+    }
+}
+
+impl CreatorList for Admin {
+    fn instance_list(&mut self) -> &mut Vec<Pubkey> {
+        &mut self.instance_list
+    }
+
+    fn space(n: usize) -> usize {
+        Admin::space(n)
+    }
+}
+
+#[derive(Accounts)]
+pub struct AlterAdmin<'info> {
+    pub manager: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"admin", config.key().as_ref(), admin.wallet.as_ref()], bump = admin.bump)]
+    pub admin: Account<'info, Admin>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+}
+
+// Only exists while pending; approving or rejecting closes it
+#[account]
+#[derive(InitSpace)]
+pub struct AdminRequest {
+    pub wallet: Pubkey,
+    #[max_len(100)]
+    pub uri: String,
+    pub created_at: i64,        // unix timestamp (seconds)
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAdmin<'info> {
+    #[account(mut)]
     pub manager: Signer<'info>,
-    #[account(seeds = [b"config"], bump = config.bump)]
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
-    #[account(seeds = [b"info"], bump = info.bump)]
-    pub info: Account<'info, Info>
+    #[account(
+        mut,
+        close = manager,
+        seeds = [b"admin", config.key().as_ref(), admin.wallet.as_ref()],
+        bump = admin.bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    // Refunded for the entries in its list
+    #[account(mut, address = admin.wallet)]
+    pub wallet: SystemAccount<'info>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+}
+
+// RequestAdmin
+#[derive(Accounts)]
+pub struct RequestAdmin<'info> {
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = requester,
+        space = ANCHOR_DISCRIMINATOR + AdminRequest::INIT_SPACE,
+        seeds = [b"admin_request", config.key().as_ref(), requester.key().as_ref()],
+        bump,
+    )]
+    pub admin_request: Account<'info, AdminRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ApproveAdmin
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct ApproveAdmin<'info> {
+    #[account(mut)]
+    pub manager: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Approval does not require a prior request; when present it is closed
+    #[account(
+        mut,
+        close = requester,
+        seeds = [b"admin_request", config.key().as_ref(), wallet.as_ref()],
+        bump = admin_request.bump,
+    )]
+    pub admin_request: Option<Account<'info, AdminRequest>>,
+    #[account(mut, address = wallet)]
+    pub requester: SystemAccount<'info>,
+
+     #[account( 
+        init, 
+        payer = manager, 
+        space = Admin::space(0),
+        seeds = [b"admin", config.key().as_ref(), wallet.as_ref()], 
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct RejectAdmin<'info> {
+    pub manager: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = requester,
+        seeds = [b"admin_request", config.key().as_ref(), wallet.as_ref()],
+        bump = admin_request.bump,
+    )]
+    pub admin_request: Account<'info, AdminRequest>,
+    #[account(mut, address = wallet)]
+    pub requester: SystemAccount<'info>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAdminRequest<'info> {
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = requester,
+        seeds = [b"admin_request", config.key().as_ref(), requester.key().as_ref()],
+        bump = admin_request.bump,
+    )]
+    pub admin_request: Account<'info, AdminRequest>,
 }
 /* ----------------------------------------------------------- */
 /* ----------------------- Captainship ----------------------- */
@@ -382,27 +1269,73 @@ pub struct Captain {
     pub wallet: Pubkey,
     #[max_len(200)]
     pub instance_list: Vec<Pubkey>,
-    pub remaining_limit: u16,
+    pub remaining_limit: u16,           // permanent slots
+    #[max_len(5)]
+    pub addon_grants: Vec<AddonGrant>,  // time-limited slots from add-on packages
     pub addon_count: u16,
     pub bump: u8,
 }
 
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct AddonGrant {
+    pub slots: u16,
+    pub expires_at: i64,        // unix timestamp (seconds)
+}
+
+impl CreatorList for Captain {
+    fn instance_list(&mut self) -> &mut Vec<Pubkey> {
+        &mut self.instance_list
+    }
+
+    fn space(n: usize) -> usize {
+        Captain::space(n)
+    }
+}
+
+impl Captain {
+    // Sized for `n` listed instances; grows and shrinks with realloc
+    pub fn space(n: usize) -> usize {
+        ANCHOR_DISCRIMINATOR + Captain::INIT_SPACE - (200 - n.min(200)) * 32
+    }
+
+    pub fn prune_grants(&mut self, now: i64) {
+        self.addon_grants.retain(|g| g.slots > 0 && g.expires_at > now);
+    }
+
+    // Spends the grant closest to expiry first, then a permanent slot
+    pub fn use_slot(&mut self, now: i64) -> Result<()> {
+        self.prune_grants(now);
+        if let Some(g) = self.addon_grants.iter_mut().min_by_key(|g| g.expires_at) {
+            g.slots -= 1;
+            return Ok(());
+        }
+        require!(self.remaining_limit > 0, ErrorCode::NotAuthorized);
+        self.remaining_limit -= 1;
+        Ok(())
+    }
+}
+
 // InitCaptain
 #[derive(Accounts)]
 pub struct InitCaptain<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(seeds = [b"info"], bump = info.bump)]
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [b"info", config.key().as_ref()], bump = info.bump)]
     pub info: Account<'info, Info>,
     
     #[account(
         init,
         payer = payer,
-        seeds = [b"captain", payer.key().as_ref()],
+        seeds = [b"captain", config.key().as_ref(), payer.key().as_ref()],
         bump,
-        space = 8 + Captain::INIT_SPACE,
+        space = Captain::space(0),
     )]
     pub captain: Account<'info, Captain>,
+    #[account(mut, seeds = [b"treasury", config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    pub token_payment: TokenPayment<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -411,10 +1344,134 @@ pub struct InitCaptain<'info> {
 pub struct GetAddOn<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(seeds = [b"info"], bump = info.bump)]
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [b"info", config.key().as_ref()], bump = info.bump)]
     pub info: Account<'info, Info>,
-    #[account(mut, seeds = [b"captain", payer.key().as_ref()], bump = captain.bump)]
+    #[account(mut, seeds = [b"captain", config.key().as_ref(), payer.key().as_ref()], bump = captain.bump)]
     pub captain: Account<'info, Captain>,
+    #[account(mut, seeds = [b"treasury", config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    pub token_payment: TokenPayment<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/* -------------------------------------------------------- */
+/* ----------------------- Treasury ----------------------- */
+/* -------------------------------------------------------- */
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RevenueSource {
+    Addon,
+    Captainship,
+    InstanceFee,
+}
+
+// Running totals for one currency
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Revenue {
+    pub addons: u64,
+    pub captainship: u64,
+    pub instance_fees: u64,
+    pub withdrawn: u64,
+}
+
+impl Revenue {
+    pub fn record(&mut self, source: RevenueSource, amount: u64) -> Result<()> {
+        let total = match source {
+            RevenueSource::Addon => &mut self.addons,
+            RevenueSource::Captainship => &mut self.captainship,
+            RevenueSource::InstanceFee => &mut self.instance_fees,
+        };
+        *total = total.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+// Club fees are paid into this PDA as plain lamports on top of its rent,
+// or into token accounts it owns when the club charges in a payment mint
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub sol: Revenue,           // lamports
+    pub tokens: Revenue,        // base units of Info.payment_mint at the time of payment
+    pub bump: u8,
+}
+
+// Token payment path, required when Info.payment_mint is set; validated in pay_tokens
+#[derive(Accounts)]
+pub struct TokenPayment<'info> {
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub payer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub treasury_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, destination: Pubkey)]
+pub struct WithdrawTreasury<'info> {
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"treasury", config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: any account may receive lamports; pinned to the `destination` argument
+    #[account(mut, address = destination)]
+    pub destination_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, destination: Pubkey)]
+pub struct WithdrawTreasuryTokens<'info> {
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"treasury", config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = destination, token::mint = mint, token::token_program = token_program)]
+    pub destination_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitTreasuryVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [b"treasury", config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"treasury_vault", config.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -433,15 +1490,81 @@ pub enum InstanceType {
 #[derive(InitSpace)]
 pub struct Instance {
     pub owner: Pubkey,
+    pub pending_owner: Option<Pubkey>,   // set by transfer_instance, cleared on accept/cancel
+    #[max_len(5)]
+    pub moderators: Vec<Pubkey>,
+    pub entry_price: u64,               // 0 = free; lamports, or base units of Info.payment_mint
     #[max_len(30)]
     pub name: String,
     pub instance_id: u64,
-    pub expires_at: i64,        // unix timestamp (seconds)
+    pub created_at: i64,        // unix timestamp (seconds)
+    pub expires_at: Option<i64>,    // unix timestamp (seconds); None = never expires (non-portfolio)
+    pub opens_at: Option<i64>,      // unix timestamp (seconds); claims before it fail; None = open now
     pub instance_type: InstanceType,
+    pub member_count: u64,              // live Membership accounts
+    pub max_consumers: u32,             // 0 = unlimited
     #[max_len(200)]
-    pub consumers: Vec<Pubkey>,
-    #[max_len(200)]
-    pub whitelist: Vec<Pubkey>,
+    pub whitelist: Vec<Pubkey>,         // sorted
+    pub whitelist_root: Option<[u8; 32]>,   // Merkle root of an off-chain whitelist; see merkle_leaf/merkle_node
+    pub bump: u8,
+}
+
+impl Instance {
+    // Sized for `n` whitelist entries; grows with realloc and keeps freed entries for reuse
+    pub fn space(n: usize) -> usize {
+        ANCHOR_DISCRIMINATOR + Instance::INIT_SPACE - (200 - n.min(200)) * 32
+    }
+
+    pub fn has_room(&self) -> bool {
+        self.max_consumers == 0 || self.member_count < self.max_consumers as u64
+    }
+}
+
+impl Membership {
+    // Joined this instance, not an earlier one closed under the same name
+    pub fn is_current(&self, inst: &Instance) -> bool {
+        self.joined_at != 0 && self.instance_id == inst.instance_id
+    }
+}
+
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MemberRole {
+    Member,     // joined through claim_instance
+    Granted,    // added by the manager or a moderator
+    Invited,    // joined with an owner-signed invite
+}
+
+// One per (instance, wallet); other programs can check membership by deriving the PDA
+// and comparing instance_id with the instance's
+#[account]
+#[derive(InitSpace)]
+pub struct Membership {
+    pub instance: Pubkey,
+    pub instance_id: u64,       // Instance.instance_id; stale once the instance is closed and its name reused
+    pub wallet: Pubkey,
+    pub role: MemberRole,
+    pub joined_at: i64,         // unix timestamp (seconds)
+    pub payer: Pubkey,          // paid the rent; refunded when the membership closes
+    pub bump: u8,
+}
+
+// Signed off-chain by the instance owner, or by the manager for Private and Whitelisted instances;
+// the Ed25519 message is the Borsh encoding of this struct
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct Invite {
+    pub instance: Pubkey,
+    pub instance_id: u64,           // Instance.instance_id; invites die with a closed instance
+    pub invitee: Option<Pubkey>,    // None = bearer invite, usable by whoever holds it
+    pub expires_at: i64,            // unix timestamp (seconds)
+    pub nonce: u64,
+}
+
+// Marks an invite nonce as spent. Seeded by the instance_id too, so a re-created instance
+// of the same name starts with fresh nonces.
+#[account]
+#[derive(InitSpace)]
+pub struct UsedInvite {
+    pub claimer: Pubkey,
     pub bump: u8,
 }
 
@@ -451,20 +1574,38 @@ pub struct Instance {
 pub struct CreateInstance<'info> {
     #[account(mut)]
     pub creator: Signer<'info>,
-    #[account(seeds = [b"config"], bump = config.bump)]
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
-    #[account(seeds = [b"info"], bump = info.bump)]
+    #[account(mut, seeds = [b"info", config.key().as_ref()], bump = info.bump)]
     pub info: Account<'info, Info>,
 
-    // Optional paths; validated in instruction logic
+    // Optional paths; validated in instruction logic. Each grows by one list entry.
+    #[account(
+        mut,
+        seeds = [b"admin", config.key().as_ref(), creator.key().as_ref()],
+        bump = admin.bump,
+        realloc = Admin::space(admin.instance_list.len() + 1),
+        realloc::payer = creator,
+        realloc::zero = false,
+    )]
     pub admin: Option<Account<'info, Admin>>,
+    #[account(
+        mut,
+        seeds = [b"captain", config.key().as_ref(), creator.key().as_ref()],
+        bump = captain.bump,
+        realloc = Captain::space(captain.instance_list.len() + 1),
+        realloc::payer = creator,
+        realloc::zero = false,
+    )]
     pub captain: Option<Account<'info, Captain>>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
 
     #[account(
         init,
         payer = creator,
-        space = ANCHOR_DISCRIMINATOR + Instance::INIT_SPACE,
-        seeds = [b"instance", name.as_bytes()],
+        space = Instance::space(0),
+        seeds = [b"instance", config.key().as_ref(), name.as_bytes()],
         bump,
     )]
     pub instance: Account<'info, Instance>,
@@ -474,11 +1615,176 @@ pub struct CreateInstance<'info> {
 
 // ClaimInstance
 #[derive(Accounts)]
+#[instruction(proof: Vec<[u8; 32]>, invite: Option<Invite>)]
 pub struct ClaimInstance<'info> {
     #[account(mut)]
     pub claimer: Signer<'info>,
-    #[account(mut, seeds = [b"instance", &instance.name.as_bytes()], bump = instance.bump)]
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [b"info", config.key().as_ref()], bump = info.bump)]
+    pub info: Account<'info, Info>,
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
     pub instance: Account<'info, Instance>,
+    // Current membership makes the claim a no-op; a stale one is taken over
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        space = ANCHOR_DISCRIMINATOR + Membership::INIT_SPACE,
+        seeds = [b"membership", instance.key().as_ref(), claimer.key().as_ref()],
+        bump
+    )]
+    pub membership: Account<'info, Membership>,
+
+    // Invite path; unused without an invite
+    #[account(
+        init,
+        payer = claimer,
+        space = ANCHOR_DISCRIMINATOR + UsedInvite::INIT_SPACE,
+        seeds = [
+            b"invite",
+            instance.key().as_ref(),
+            &instance.instance_id.to_le_bytes(),
+            &invite.as_ref().map_or(0, |i| i.nonce).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub used_invite: Option<Account<'info, UsedInvite>>,
+    /// CHECK: the instructions sysvar, pinned by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Paid entry; unused when Instance.entry_price is 0
+    #[account(mut, seeds = [b"treasury", config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut, address = instance.owner)]
+    pub owner: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_payment: TokenPayment<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// LeaveInstance
+#[derive(Accounts)]
+pub struct LeaveInstance<'info> {
+    pub member: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: pinned by the membership seeds; may be closed or re-created, read in leave_instance
+    #[account(mut)]
+    pub instance: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"membership", instance.key().as_ref(), member.key().as_ref()],
+        bump = membership.bump
+    )]
+    pub membership: Account<'info, Membership>,
+    #[account(mut, address = membership.payer)]
+    pub payer: SystemAccount<'info>,
+}
+
+// OwnerInstance
+#[derive(Accounts)]
+pub struct OwnerInstance<'info> {
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
+    pub instance: Account<'info, Instance>,
+}
+
+// AcceptInstance
+#[derive(Accounts)]
+pub struct AcceptInstance<'info> {
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
+    pub instance: Account<'info, Instance>,
+    // Refunded for the entries dropped from their lists
+    #[account(mut, address = instance.owner)]
+    pub old_owner: SystemAccount<'info>,
+
+    // Creator lists of the current owner. Always passed so the key can't be left behind;
+    // either may not exist.
+    /// CHECK: the current owner's Admin PDA, pinned by seeds; read in unlist_instance
+    #[account(mut, seeds = [b"admin", config.key().as_ref(), instance.owner.as_ref()], bump)]
+    pub old_admin: UncheckedAccount<'info>,
+    /// CHECK: the current owner's Captain PDA, pinned by seeds; read in unlist_instance
+    #[account(mut, seeds = [b"captain", config.key().as_ref(), instance.owner.as_ref()], bump)]
+    pub old_captain: UncheckedAccount<'info>,
+
+    // Creator lists of the incoming owner; one is required for portfolios
+    #[account(
+        mut,
+        seeds = [b"admin", config.key().as_ref(), new_owner.key().as_ref()],
+        bump = new_admin.bump,
+        realloc = Admin::space(new_admin.instance_list.len() + 1),
+        realloc::payer = new_owner,
+        realloc::zero = false,
+    )]
+    pub new_admin: Option<Account<'info, Admin>>,
+    #[account(
+        mut,
+        seeds = [b"captain", config.key().as_ref(), new_owner.key().as_ref()],
+        bump = new_captain.bump,
+        realloc = Captain::space(new_captain.instance_list.len() + 1),
+        realloc::payer = new_owner,
+        realloc::zero = false,
+    )]
+    pub new_captain: Option<Account<'info, Captain>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ExtendInstance
+#[derive(Accounts)]
+pub struct ExtendInstance<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [b"info", config.key().as_ref()], bump = info.bump)]
+    pub info: Account<'info, Info>,
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
+    pub instance: Account<'info, Instance>,
+    #[account(mut, seeds = [b"treasury", config.key().as_ref()], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    pub token_payment: TokenPayment<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// CloseInstance
+#[derive(Accounts)]
+pub struct CloseInstance<'info> {
+    // Instance owner, or the manager (council member with a proposal)
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()],
+        bump = instance.bump,
+    )]
+    pub instance: Account<'info, Instance>,
+    #[account(mut, address = instance.owner)]
+    pub owner: SystemAccount<'info>,
+
+    // Creator lists of the owner. Always passed so the key can't be left behind;
+    // either may not exist. The freed entry is refunded to the owner.
+    /// CHECK: the owner's Admin PDA, pinned by seeds; read in unlist_instance
+    #[account(mut, seeds = [b"admin", config.key().as_ref(), instance.owner.as_ref()], bump)]
+    pub owner_admin: UncheckedAccount<'info>,
+    /// CHECK: the owner's Captain PDA, pinned by seeds; read in unlist_instance
+    #[account(mut, seeds = [b"captain", config.key().as_ref(), instance.owner.as_ref()], bump)]
+    pub owner_captain: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
     pub system_program: Program<'info, System>,
 }
 
@@ -486,15 +1792,245 @@ pub struct ClaimInstance<'info> {
 #[derive(Accounts)]
 
 pub struct SetInstance<'info> {
-    pub manager: Signer<'info>,
-    #[account(seeds = [b"config"], bump = config.bump)]
+    // Manager (or council member with a proposal), or the instance's owner or moderators depending on the instruction
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
+    pub instance: Account<'info, Instance>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+    pub system_program: Program<'info, System>,
+}
+
+// AddWhitelist
+#[derive(Accounts)]
+pub struct AddWhitelist<'info> {
+    // Manager (or council member with a proposal), or one of the instance's moderators; pays for the new entry
+    // unless space freed by remove_whitelist is left
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()],
+        bump = instance.bump,
+        realloc = Instance::space(instance.whitelist.len() + 1).max(instance.to_account_info().data_len()),
+        realloc::payer = signer,
+        realloc::zero = false,
+    )]
+    pub instance: Account<'info, Instance>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+    pub system_program: Program<'info, System>,
+}
+
+// AddWhitelistBatch
+#[derive(Accounts)]
+#[instruction(wallets: Vec<Pubkey>)]
+pub struct AddWhitelistBatch<'info> {
+    // Manager (or council member with a proposal), or one of the instance's moderators; pays for the new entries
+    // beyond space freed by remove_whitelist
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()],
+        bump = instance.bump,
+        realloc = Instance::space(instance.whitelist.len() + wallets.len()).max(instance.to_account_info().data_len()),
+        realloc::payer = signer,
+        realloc::zero = false,
+    )]
+    pub instance: Account<'info, Instance>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+    pub system_program: Program<'info, System>,
+}
+
+// RemoveWhitelist
+#[derive(Accounts)]
+pub struct RemoveWhitelist<'info> {
+    // Manager (or council member with a proposal), or one of the instance's moderators.
+    // The account keeps its size: the freed entry's rent stays for the next add rather than
+    // going to a signer who may not have paid it.
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
     pub config: Account<'info, Config>,
 
-    #[account(mut, seeds = [b"instance", &instance.name.as_bytes()], bump = instance.bump)]
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
     pub instance: Account<'info, Instance>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
     pub system_program: Program<'info, System>,
 }
 
+// GrantInstance
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct GrantInstance<'info> {
+    // Manager (or council member with a proposal), or one of the instance's moderators; pays the membership rent
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
+    pub instance: Account<'info, Instance>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + Membership::INIT_SPACE,
+        seeds = [b"membership", instance.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub membership: Account<'info, Membership>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+    pub system_program: Program<'info, System>,
+}
+
+// RemoveConsumer
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct RemoveConsumer<'info> {
+    // Instance owner, one of its moderators, or the manager (or council member with a proposal)
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"instance", config.key().as_ref(), instance.name.as_bytes()], bump = instance.bump)]
+    pub instance: Account<'info, Instance>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"membership", instance.key().as_ref(), wallet.as_ref()],
+        bump = membership.bump
+    )]
+    pub membership: Account<'info, Membership>,
+    #[account(mut, address = membership.payer)]
+    pub payer: SystemAccount<'info>,
+    #[account(mut)]
+    pub proposal: Option<Account<'info, Proposal>>,
+}
+
+/* ------------------------------------------------------- */
+/* ----------------------- Council ----------------------- */
+/* ------------------------------------------------------- */
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ManagerAction {
+    QueueChange { change: ConfigChange },
+    CancelChange { change: ConfigChange },
+    ApproveAdmin { wallet: Pubkey },
+    RejectAdmin { wallet: Pubkey },
+    SetAdminStatus { wallet: Pubkey, status: bool },
+    RevokeAdmin { wallet: Pubkey },
+    SetPause { paused: u8 },
+    CancelManager { new_manager: Pubkey },
+    WithdrawTreasury { amount: u64, destination: Pubkey },
+    WithdrawTreasuryTokens { mint: Pubkey, amount: u64, destination: Pubkey },
+    CreateInstance {
+        instance_type: InstanceType,
+        #[max_len(30)]
+        name: String,
+        entry_price: u64,
+        max_consumers: u32,
+        opens_at: Option<i64>,
+    },
+    CloseInstance { instance: Pubkey },
+    SetEntryPrice { instance: Pubkey, entry_price: u64 },
+    SetOpensAt { instance: Pubkey, opens_at: Option<i64> },
+    GrantPrivateInstance { instance: Pubkey, wallet: Pubkey },
+    AddWhitelist { instance: Pubkey, wallet: Pubkey },
+    AddWhitelistBatch {
+        instance: Pubkey,
+        #[max_len(20)]
+        wallets: Vec<Pubkey>,
+    },
+    RemoveWhitelist { instance: Pubkey, wallet: Pubkey },
+    SetWhitelistRoot { instance: Pubkey, root: Option<[u8; 32]> },
+    RemoveConsumer { instance: Pubkey, wallet: Pubkey },
+    AddModerator { instance: Pubkey, wallet: Pubkey },
+    RemoveModerator { instance: Pubkey, wallet: Pubkey },
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub config: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: ManagerAction,
+    #[max_len(10)]
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(mut, seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = ANCHOR_DISCRIMINATOR + Proposal::INIT_SPACE,
+        seeds = [b"proposal", config.key().as_ref(), &config.proposal_ids.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub member: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"proposal", config.key().as_ref(), &proposal.proposal_id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(seeds = [b"config", config.club.as_ref()], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = proposer,
+        has_one = proposer,
+        seeds = [b"proposal", config.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+/* -------------------------------------------------------------------- */
+/* ------------------------------ Events ------------------------------ */
+/* -------------------------------------------------------------------- */
+#[event]
+pub struct ConsumerLeft {
+    pub instance: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct ConsumerRemoved {
+    pub instance: Pubkey,
+    pub wallet: Pubkey,
+    pub removed_by: Pubkey,
+}
 
 /* -------------------------------------------------------------------- */
 /* ----------------------------- Utilities ---------------------------- */
@@ -504,6 +2040,257 @@ fn is_manager(cfg: &Account<Config>, signer: &Signer) -> Result<()> {
     Ok(())
 }
 
+// Checked when queued so a bad change fails fast instead of after the delay
+fn validate_change(cfg: &Config, change: &ConfigChange) -> Result<()> {
+    match change {
+        ConfigChange::Manager { new_manager } => {
+            require!(cfg.manager != *new_manager, ErrorCode::ExistingValue);
+        }
+        ConfigChange::Council { members, threshold } => {
+            require!(members.len() <= 10, ErrorCode::LimitExceeded);
+            require!(*threshold as usize <= members.len(), ErrorCode::InvalidThreshold);
+            require!(members.is_empty() == (*threshold == 0), ErrorCode::InvalidThreshold);
+            for (i, m) in members.iter().enumerate() {
+                require!(!members[..i].contains(m), ErrorCode::ExistingValue);
+            }
+        }
+        ConfigChange::PortfolioDuration { min_days, max_days } => {
+            require!(*min_days >= 1 && min_days <= max_days, ErrorCode::InvalidDuration);
+        }
+        ConfigChange::ProtocolFee { bps } => {
+            require!(*bps as u64 <= BPS_DENOMINATOR, ErrorCode::LimitExceeded);
+        }
+        ConfigChange::TimelockDelay { delay } => {
+            require!(*delay >= 0 && *delay <= MAX_TIMELOCK_DELAY, ErrorCode::LimitExceeded);
+        }
+        ConfigChange::AddonPackages { packages } => {
+            require!(packages.len() <= 5, ErrorCode::LimitExceeded);
+            require!(packages.iter().all(|p| p.slots > 0), ErrorCode::InvalidValue);
+        }
+        ConfigChange::InstanceLimit { .. }
+        | ConfigChange::PaymentToken { .. }
+        | ConfigChange::PortfolioExtension { .. }
+        | ConfigChange::MaxConsumers { .. }
+        | ConfigChange::CaptainshipPrice { .. } => {}
+    }
+    Ok(())
+}
+
+// Protocol fee (rounded down) and the owner's share of an entry price
+fn split_entry_price(price: u64, fee_bps: u16) -> (u64, u64) {
+    let fee = (price as u128 * fee_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+    (fee, price - fee)
+}
+
+// Charges the club price in the active currency and books it in the treasury totals
+fn collect_fee<'info>(
+    stt: &Info,
+    lamports: u64,
+    tokens: u64,
+    source: RevenueSource,
+    payer: &Signer<'info>,
+    treasury: &mut Account<'info, Treasury>,
+    token_payment: &TokenPayment<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    match stt.payment_mint {
+        Some(mint) => {
+            let vault = token_payment.treasury_vault.as_ref();
+            pay_tokens(mint, tokens, payer, vault, treasury.key(), token_payment)?;
+            treasury.tokens.record(source, tokens)
+        }
+        None => {
+            pay_lamports(lamports, payer, &treasury.to_account_info(), system_program)?;
+            treasury.sol.record(source, lamports)
+        }
+    }
+}
+
+fn pay_lamports<'info>(
+    amount: u64,
+    payer: &Signer<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    require!(payer.lamports() >= amount, ErrorCode::InsufficientFunds);
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: to.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+// Any token account of the mint owned by `to_owner` is accepted as the destination.
+// For the treasury only the program can sign for the PDA, so funds can't be redirected.
+fn pay_tokens<'info>(
+    expected_mint: Pubkey,
+    amount: u64,
+    payer: &Signer<'info>,
+    to: Option<&InterfaceAccount<'info, TokenAccount>>,
+    to_owner: Pubkey,
+    token_payment: &TokenPayment<'info>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let mint = token_payment.payment_mint.as_ref().ok_or(ErrorCode::PaymentAccountsRequired)?;
+    let from = token_payment.payer_token_account.as_ref().ok_or(ErrorCode::PaymentAccountsRequired)?;
+    let to = to.ok_or(ErrorCode::PaymentAccountsRequired)?;
+    let token_program = token_payment.token_program.as_ref().ok_or(ErrorCode::PaymentAccountsRequired)?;
+
+    require!(mint.key() == expected_mint, ErrorCode::InvalidMint);
+    require!(to.mint == expected_mint, ErrorCode::InvalidMint);
+    require!(to.owner == to_owner, ErrorCode::InvalidRecipient);
+    require!(from.amount >= amount, ErrorCode::InsufficientFunds);
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: payer.to_account_info(),
+            },
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+// Accounts that list the instances their wallet created
+trait CreatorList {
+    fn instance_list(&mut self) -> &mut Vec<Pubkey>;
+    fn space(n: usize) -> usize;
+}
+
+// Drops `key` from a creator list if that account exists, refunding the freed rent to `refund`
+fn unlist_instance<T>(info: &AccountInfo, refund: &AccountInfo, key: &Pubkey) -> Result<()>
+where
+    T: CreatorList + AccountSerialize + AccountDeserialize,
+{
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(());
+    }
+    let mut list = T::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    list.instance_list().retain(|k| k != key);
+
+    let space = T::space(list.instance_list().len());
+    let excess = info.lamports().saturating_sub(Rent::get()?.minimum_balance(space));
+    **info.try_borrow_mut_lamports()? -= excess;
+    **refund.try_borrow_mut_lamports()? += excess;
+    info.realloc(space, false)?;
+    list.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+// Private and Whitelisted instances are run by the manager, not their owner
+fn is_owner_run(inst: &Instance) -> bool {
+    matches!(inst.instance_type, InstanceType::Portfolio | InstanceType::Public)
+}
+
+fn is_moderator(inst: &Account<Instance>, signer: &Signer) -> bool {
+    inst.moderators.contains(&signer.key())
+}
+
+fn not_paused(cfg: &Account<Config>, flag: u8) -> Result<()> {
+    require!(cfg.paused & flag == 0, ErrorCode::Paused);
+    Ok(())
+}
+
+fn is_council(cfg: &Account<Config>, signer: &Signer) -> Result<()> {
+    require!(cfg.threshold > 0, ErrorCode::CouncilDisabled);
+    require!(cfg.council.contains(&signer.key()), ErrorCode::NotCouncil);
+    Ok(())
+}
+
+// Leaves and nodes are domain-separated so a node can't pass as a leaf
+pub fn merkle_leaf(wallet: &Pubkey) -> [u8; 32] {
+    hashv(&[&[0], wallet.as_ref()]).to_bytes()
+}
+
+// Children are hashed in sorted order, so proofs carry no left/right flags
+pub fn merkle_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[&[1], lo, hi]).to_bytes()
+}
+
+fn verify_proof(root: [u8; 32], wallet: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let hash = proof.iter().fold(merkle_leaf(wallet), |acc, sibling| merkle_node(&acc, sibling));
+    hash == root
+}
+
+// Same rule as the owner bypasses: manager-run instances take invites from the manager,
+// and a council has no single key to sign them
+fn invite_signer(cfg: &Config, inst: &Instance) -> Result<Pubkey> {
+    if is_owner_run(inst) {
+        return Ok(inst.owner);
+    }
+    require!(cfg.threshold == 0, ErrorCode::InvalidInvite);
+    Ok(cfg.manager)
+}
+
+// Expects the Ed25519 precompile instruction right before this one
+fn verify_invite(ixs: &AccountInfo, signer: &Pubkey, invite: &Invite) -> Result<()> {
+    let current = load_current_index_checked(ixs)? as usize;
+    require!(current > 0, ErrorCode::InvalidInvite);
+    let ix = load_instruction_at_checked(current - 1, ixs)?;
+    check_ed25519_ix(&ix, signer, &anchor_lang::prelude::borsh::to_vec(invite)?)
+}
+
+// The precompile already verified the signature; this checks it is a single one by
+// `signer` over `message`, with all data inline
+fn check_ed25519_ix(ix: &Instruction, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    require!(ix.program_id == ed25519_program::ID, ErrorCode::InvalidInvite);
+
+    // [count, padding, signature offset/index, public key offset/index, message offset/size/index]
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::InvalidInvite);
+    let read = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let inline = [read(4), read(8), read(14)].iter().all(|i| *i == u16::MAX);
+    require!(inline, ErrorCode::InvalidInvite);
+
+    let (key_at, msg_at, msg_len) = (read(6) as usize, read(10) as usize, read(12) as usize);
+    let key = data.get(key_at..key_at + 32).ok_or(ErrorCode::InvalidInvite)?;
+    let msg = data.get(msg_at..msg_at + msg_len).ok_or(ErrorCode::InvalidInvite)?;
+    require!(key == signer.as_ref(), ErrorCode::InvalidInvite);
+    require!(msg == message, ErrorCode::InvalidInvite);
+    Ok(())
+}
+
+// Single-key mode: the manager signs. Council mode: a council member executes
+// a proposal carrying the same action once it has `threshold` approvals.
+fn is_authorized(
+    cfg: &Account<Config>,
+    signer: &Signer,
+    proposal: Option<&mut Account<Proposal>>,
+    action: ManagerAction,
+) -> Result<()> {
+    if cfg.threshold == 0 {
+        return is_manager(cfg, signer);
+    }
+    is_council(cfg, signer)?;
+
+    let proposal = proposal.ok_or(ErrorCode::ProposalRequired)?;
+    require!(proposal.config == cfg.key(), ErrorCode::ProposalMismatch);
+    require!(!proposal.executed, ErrorCode::ProposalExecuted);
+    require!(proposal.action == action, ErrorCode::ProposalMismatch);
+
+    // Only approvals from current council members count
+    let approvals = proposal.approvals.iter().filter(|k| cfg.council.contains(k)).count();
+    require!(approvals >= cfg.threshold as usize, ErrorCode::ThresholdNotMet);
+
+    proposal.executed = true;
+    Ok(())
+}
+
 /* -------------------------------------------------------------------- */
 /* ------------------------------ Errors ------------------------------ */
 /* -------------------------------------------------------------------- */
@@ -530,5 +2317,352 @@ pub enum ErrorCode {
     #[msg("Expired")]
     Expired,
     #[msg("Existing Value")]
-    ExistingValue
+    ExistingValue,
+    #[msg("No Pending Manager")]
+    NoPendingManager,
+    #[msg("Not Pending Manager")]
+    NotPendingManager,
+    #[msg("Invalid Threshold")]
+    InvalidThreshold,
+    #[msg("Council Disabled")]
+    CouncilDisabled,
+    #[msg("Only Council")]
+    NotCouncil,
+    #[msg("Proposal Required")]
+    ProposalRequired,
+    #[msg("Proposal Mismatch")]
+    ProposalMismatch,
+    #[msg("Proposal Already Executed")]
+    ProposalExecuted,
+    #[msg("Already Approved")]
+    AlreadyApproved,
+    #[msg("Threshold Not Met")]
+    ThresholdNotMet,
+    #[msg("Timelock Active")]
+    TimelockActive,
+    #[msg("Invalid Value")]
+    InvalidValue,
+    #[msg("Paused")]
+    Paused,
+    #[msg("No Pending Owner")]
+    NoPendingOwner,
+    #[msg("Not Pending Owner")]
+    NotPendingOwner,
+    #[msg("Not Found")]
+    NotFound,
+    #[msg("Payment Accounts Required")]
+    PaymentAccountsRequired,
+    #[msg("Invalid Mint")]
+    InvalidMint,
+    #[msg("Invalid Recipient")]
+    InvalidRecipient,
+    #[msg("Invalid Duration")]
+    InvalidDuration,
+    #[msg("Instance Is Full")]
+    InstanceFull,
+    #[msg("Instance Not Yet Open")]
+    NotYetOpen,
+    #[msg("Invalid Invite")]
+    InvalidInvite
+}
+
+/* -------------------------------------------------------------------- */
+/* ------------------------------- Tests ------------------------------ */
+/* -------------------------------------------------------------------- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Error code of a failed check, None on success
+    fn code(res: Result<()>) -> Option<u32> {
+        match res {
+            Ok(()) => None,
+            Err(Error::AnchorError(e)) => Some(e.error_code_number),
+            Err(e) => panic!("unexpected error: {e:?}"),
+        }
+    }
+
+    fn err(e: ErrorCode) -> Option<u32> {
+        Some(e.into())
+    }
+
+    fn config(manager: Pubkey) -> Config {
+        Config {
+            initialized: true,
+            club: Pubkey::new_unique(),
+            manager,
+            pending_manager: None,
+            council: Vec::new(),
+            threshold: 0,
+            proposal_ids: 0,
+            timelock_delay: 0,
+            paused: 0,
+            name: String::new(),
+            title: String::new(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn validate_manager_change() {
+        let manager = Pubkey::new_unique();
+        let cfg = config(manager);
+
+        let same = ConfigChange::Manager { new_manager: manager };
+        assert_eq!(code(validate_change(&cfg, &same)), err(ErrorCode::ExistingValue));
+        let other = ConfigChange::Manager { new_manager: Pubkey::new_unique() };
+        assert_eq!(code(validate_change(&cfg, &other)), None);
+    }
+
+    #[test]
+    fn validate_council_change() {
+        let cfg = config(Pubkey::new_unique());
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let council = |members: Vec<Pubkey>, threshold| ConfigChange::Council { members, threshold };
+
+        assert_eq!(code(validate_change(&cfg, &council(vec![a, b], 2))), None);
+        assert_eq!(code(validate_change(&cfg, &council(vec![], 0))), None);
+        assert_eq!(code(validate_change(&cfg, &council(vec![a, b], 3))), err(ErrorCode::InvalidThreshold));
+        assert_eq!(code(validate_change(&cfg, &council(vec![a], 0))), err(ErrorCode::InvalidThreshold));
+        assert_eq!(code(validate_change(&cfg, &council(vec![a, a], 1))), err(ErrorCode::ExistingValue));
+
+        let eleven = (0..11).map(|_| Pubkey::new_unique()).collect();
+        assert_eq!(code(validate_change(&cfg, &council(eleven, 1))), err(ErrorCode::LimitExceeded));
+    }
+
+    #[test]
+    fn validate_ranges() {
+        let cfg = config(Pubkey::new_unique());
+        let check = |change: ConfigChange| code(validate_change(&cfg, &change));
+
+        assert_eq!(check(ConfigChange::PortfolioDuration { min_days: 1, max_days: 1 }), None);
+        assert_eq!(check(ConfigChange::PortfolioDuration { min_days: 0, max_days: 5 }), err(ErrorCode::InvalidDuration));
+        assert_eq!(check(ConfigChange::PortfolioDuration { min_days: 6, max_days: 5 }), err(ErrorCode::InvalidDuration));
+
+        assert_eq!(check(ConfigChange::ProtocolFee { bps: BPS_DENOMINATOR as u16 }), None);
+        assert_eq!(check(ConfigChange::ProtocolFee { bps: BPS_DENOMINATOR as u16 + 1 }), err(ErrorCode::LimitExceeded));
+
+        assert_eq!(check(ConfigChange::TimelockDelay { delay: MAX_TIMELOCK_DELAY }), None);
+        assert_eq!(check(ConfigChange::TimelockDelay { delay: MAX_TIMELOCK_DELAY + 1 }), err(ErrorCode::LimitExceeded));
+        assert_eq!(check(ConfigChange::TimelockDelay { delay: -1 }), err(ErrorCode::LimitExceeded));
+    }
+
+    #[test]
+    fn validate_addon_packages() {
+        let cfg = config(Pubkey::new_unique());
+        let package = |slots| AddonPackage { slots, price_lamports: 0, price_tokens: 0, validity_days: 0 };
+        let check = |packages: Vec<AddonPackage>| code(validate_change(&cfg, &ConfigChange::AddonPackages { packages }));
+
+        assert_eq!(check(vec![package(1); 5]), None);
+        assert_eq!(check(vec![package(1); 6]), err(ErrorCode::LimitExceeded));
+        assert_eq!(check(vec![package(1), package(0)]), err(ErrorCode::InvalidValue));
+    }
+
+    #[test]
+    fn revenue_record() {
+        let mut revenue = Revenue::default();
+        revenue.record(RevenueSource::Addon, 5).unwrap();
+        revenue.record(RevenueSource::Addon, 7).unwrap();
+        revenue.record(RevenueSource::Captainship, 3).unwrap();
+        revenue.record(RevenueSource::InstanceFee, 11).unwrap();
+
+        assert_eq!((revenue.addons, revenue.captainship, revenue.instance_fees), (12, 3, 11));
+        assert_eq!(revenue.withdrawn, 0);
+
+        // Overflow fails and leaves the total untouched
+        assert_eq!(code(revenue.record(RevenueSource::Addon, u64::MAX)), err(ErrorCode::Overflow));
+        assert_eq!(revenue.addons, 12);
+    }
+
+    #[test]
+    fn entry_price_split() {
+        assert_eq!(split_entry_price(1_000, 0), (0, 1_000));
+        assert_eq!(split_entry_price(1_000, 250), (25, 975));
+        assert_eq!(split_entry_price(1_000, BPS_DENOMINATOR as u16), (1_000, 0));
+
+        // The fee rounds down, so the owner keeps the remainder
+        assert_eq!(split_entry_price(99, 100), (0, 99));
+        assert_eq!(split_entry_price(199, 5_000), (99, 100));
+
+        // No overflow at the top of the range
+        assert_eq!(split_entry_price(u64::MAX, 5_000), (u64::MAX / 2, u64::MAX - u64::MAX / 2));
+    }
+
+    fn captain(remaining_limit: u16, addon_grants: Vec<AddonGrant>) -> Captain {
+        Captain {
+            wallet: Pubkey::new_unique(),
+            instance_list: Vec::new(),
+            remaining_limit,
+            addon_grants,
+            addon_count: 0,
+            bump: 0,
+        }
+    }
+
+    fn grants(cap: &Captain) -> Vec<(u16, i64)> {
+        cap.addon_grants.iter().map(|g| (g.slots, g.expires_at)).collect()
+    }
+
+    #[test]
+    fn prune_grants_drops_spent_and_expired() {
+        let mut cap = captain(0, vec![
+            AddonGrant { slots: 2, expires_at: 100 },
+            AddonGrant { slots: 0, expires_at: 500 },
+            AddonGrant { slots: 1, expires_at: 50 },
+            AddonGrant { slots: 3, expires_at: 101 },
+        ]);
+        cap.prune_grants(100);
+
+        assert_eq!(grants(&cap), vec![(3, 101)]);
+    }
+
+    #[test]
+    fn use_slot_spends_soonest_grant_first() {
+        let mut cap = captain(1, vec![
+            AddonGrant { slots: 1, expires_at: 300 },
+            AddonGrant { slots: 1, expires_at: 200 },
+        ]);
+
+        cap.use_slot(100).unwrap();
+        assert_eq!(grants(&cap), vec![(1, 300), (0, 200)]);
+
+        cap.use_slot(100).unwrap();
+        assert_eq!(grants(&cap), vec![(0, 300)]);
+        assert_eq!(cap.remaining_limit, 1);
+
+        // Grants gone, so the permanent slot goes next
+        cap.use_slot(100).unwrap();
+        assert!(cap.addon_grants.is_empty());
+        assert_eq!(cap.remaining_limit, 0);
+
+        assert_eq!(code(cap.use_slot(100)), err(ErrorCode::NotAuthorized));
+    }
+
+    #[test]
+    fn use_slot_ignores_expired_grants() {
+        let mut cap = captain(0, vec![AddonGrant { slots: 5, expires_at: 100 }]);
+
+        assert_eq!(code(cap.use_slot(100)), err(ErrorCode::NotAuthorized));
+        assert!(cap.addon_grants.is_empty());
+    }
+
+    fn instance(instance_id: u64) -> Instance {
+        Instance {
+            owner: Pubkey::new_unique(),
+            pending_owner: None,
+            moderators: Vec::new(),
+            entry_price: 0,
+            name: String::new(),
+            instance_id,
+            created_at: 0,
+            expires_at: None,
+            opens_at: None,
+            instance_type: InstanceType::Public,
+            member_count: 0,
+            max_consumers: 0,
+            whitelist: Vec::new(),
+            whitelist_root: None,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn membership_of_closed_instance_is_stale() {
+        let mut mbr = Membership {
+            instance: Pubkey::new_unique(),
+            instance_id: 3,
+            wallet: Pubkey::new_unique(),
+            role: MemberRole::Member,
+            joined_at: 0,
+            payer: Pubkey::new_unique(),
+            bump: 0,
+        };
+        assert!(!mbr.is_current(&instance(3)));
+
+        mbr.joined_at = 100;
+        assert!(mbr.is_current(&instance(3)));
+        assert!(!mbr.is_current(&instance(4)));
+    }
+
+    #[test]
+    fn invite_signer_follows_who_runs_the_instance() {
+        let manager = Pubkey::new_unique();
+        let mut cfg = config(manager);
+        let mut inst = instance(1);
+
+        assert_eq!(invite_signer(&cfg, &inst).unwrap(), inst.owner);
+        inst.instance_type = InstanceType::Private;
+        assert_eq!(invite_signer(&cfg, &inst).unwrap(), manager);
+
+        cfg.council = vec![Pubkey::new_unique()];
+        cfg.threshold = 1;
+        assert_eq!(code(invite_signer(&cfg, &inst).map(|_| ())), err(ErrorCode::InvalidInvite));
+        inst.instance_type = InstanceType::Portfolio;
+        assert_eq!(invite_signer(&cfg, &inst).unwrap(), inst.owner);
+    }
+
+    // Precompile instruction signed by a fixed test key
+    fn ed25519_ix(message: &[u8]) -> (Instruction, Pubkey) {
+        use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+
+        let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+        let keypair = Keypair { public: PublicKey::from(&secret), secret };
+        let signature = keypair.sign(message).to_bytes();
+        let pubkey = keypair.public.to_bytes();
+
+        let ix = solana_ed25519_program::new_ed25519_instruction_with_signature(message, &signature, &pubkey);
+        (ix, Pubkey::new_from_array(pubkey))
+    }
+
+    #[test]
+    fn ed25519_valid_signature() {
+        let invite = Invite { instance: Pubkey::new_unique(), instance_id: 1, invitee: None, expires_at: 100, nonce: 1 };
+        let message = anchor_lang::prelude::borsh::to_vec(&invite).unwrap();
+        let (ix, signer) = ed25519_ix(&message);
+
+        // The runtime precompile accepts it too
+        let features = solana_feature_set::FeatureSet::all_enabled();
+        assert!(solana_ed25519_program::verify(&ix.data, &[&ix.data], &features).is_ok());
+
+        assert_eq!(code(check_ed25519_ix(&ix, &signer, &message)), None);
+    }
+
+    #[test]
+    fn ed25519_wrong_key_or_message() {
+        let (ix, signer) = ed25519_ix(b"invite");
+
+        assert_eq!(code(check_ed25519_ix(&ix, &Pubkey::new_unique(), b"invite")), err(ErrorCode::InvalidInvite));
+        assert_eq!(code(check_ed25519_ix(&ix, &signer, b"invitE")), err(ErrorCode::InvalidInvite));
+        assert_eq!(code(check_ed25519_ix(&ix, &signer, b"invite+")), err(ErrorCode::InvalidInvite));
+    }
+
+    #[test]
+    fn ed25519_wrong_program() {
+        let (mut ix, signer) = ed25519_ix(b"invite");
+        ix.program_id = Pubkey::new_unique();
+
+        assert_eq!(code(check_ed25519_ix(&ix, &signer, b"invite")), err(ErrorCode::InvalidInvite));
+    }
+
+    #[test]
+    fn ed25519_data_in_other_instructions() {
+        // Signature, public key and message instruction indices
+        for at in [4, 8, 14] {
+            let (mut ix, signer) = ed25519_ix(b"invite");
+            ix.data[at..at + 2].copy_from_slice(&0u16.to_le_bytes());
+
+            assert_eq!(code(check_ed25519_ix(&ix, &signer, b"invite")), err(ErrorCode::InvalidInvite));
+        }
+    }
+
+    #[test]
+    fn ed25519_truncated_data() {
+        let (ix, signer) = ed25519_ix(b"invite");
+        for len in [0, 1, 15, 16, 47, ix.data.len() - 1] {
+            let mut short = ix.clone();
+            short.data.truncate(len);
+
+            assert_eq!(code(check_ed25519_ix(&short, &signer, b"invite")), err(ErrorCode::InvalidInvite));
+        }
+    }
 }
@@ -0,0 +1,119 @@
+//! Off-chain helpers for Merkle-root whitelists.
+//!
+//! Build the root for `set_whitelist_root` and the per-wallet proofs passed
+//! to `claim_instance`. Hashing matches the on-chain `merkle_leaf` and
+//! `merkle_node`, so anything produced here verifies in the program.
+
+use crate::{merkle_leaf, merkle_node};
+use anchor_lang::prelude::Pubkey;
+
+/// All tree levels, leaves first. Leaves are sorted and deduplicated; an odd
+/// node at the end of a level is carried up unchanged.
+fn levels(wallets: &[Pubkey]) -> Vec<Vec<[u8; 32]>> {
+    let mut leaves: Vec<[u8; 32]> = wallets.iter().map(merkle_leaf).collect();
+    leaves.sort();
+    leaves.dedup();
+
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => merkle_node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Root of the whitelist, or `None` if it is empty.
+pub fn build_root(wallets: &[Pubkey]) -> Option<[u8; 32]> {
+    levels(wallets).last().unwrap().first().copied()
+}
+
+/// Proof that `wallet` is on the whitelist, or `None` if it isn't.
+pub fn build_proof(wallets: &[Pubkey], wallet: &Pubkey) -> Option<Vec<[u8; 32]>> {
+    let levels = levels(wallets);
+    let mut index = levels[0].binary_search(&merkle_leaf(wallet)).ok()?;
+
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    Some(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify_proof;
+
+    fn wallets(n: usize) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    // Every listed wallet's proof verifies against the root
+    fn assert_all_verify(list: &[Pubkey]) {
+        let root = build_root(list).unwrap();
+        for wallet in list {
+            let proof = build_proof(list, wallet).unwrap();
+            assert!(verify_proof(root, wallet, &proof));
+        }
+    }
+
+    #[test]
+    fn single_wallet() {
+        let list = wallets(1);
+        assert_eq!(build_root(&list), Some(crate::merkle_leaf(&list[0])));
+        assert_eq!(build_proof(&list, &list[0]), Some(Vec::new()));
+        assert_all_verify(&list);
+    }
+
+    #[test]
+    fn two_wallets() {
+        assert_all_verify(&wallets(2));
+    }
+
+    #[test]
+    fn odd_and_even_sizes() {
+        for n in [3, 4, 5, 7, 8, 13, 64, 100] {
+            assert_all_verify(&wallets(n));
+        }
+    }
+
+    #[test]
+    fn duplicates_are_ignored() {
+        let list = wallets(5);
+        let mut doubled = list.clone();
+        doubled.extend(list.iter().rev());
+
+        assert_eq!(build_root(&doubled), build_root(&list));
+        assert_all_verify(&doubled);
+    }
+
+    #[test]
+    fn missing_wallet() {
+        let list = wallets(6);
+        let outsider = Pubkey::new_unique();
+        assert_eq!(build_proof(&list, &outsider), None);
+
+        // A listed wallet's proof doesn't carry over to another wallet
+        let root = build_root(&list).unwrap();
+        let proof = build_proof(&list, &list[0]).unwrap();
+        assert!(!verify_proof(root, &outsider, &proof));
+    }
+
+    #[test]
+    fn empty_list() {
+        assert_eq!(build_root(&[]), None);
+        assert_eq!(build_proof(&[], &Pubkey::new_unique()), None);
+    }
+}
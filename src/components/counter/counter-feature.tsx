@@ -41,26 +41,34 @@ export default function CounterFeature() {
   )
 }
 
-// info - init_config, set_manager
+// info - init_config, queue_change (manager), accept_manager, cancel_manager_proposal
 const SlnConfig = () => {
   const { state, fn } = useSlnConfig()
 
   const onInitConfig = async () => {
     await fn.initConfig.mutateAsync()
   }
-  const onSetManager = async () => {
-    await fn.setManager()
+  const onQueueManagerChange = async () => {
+    await fn.queueManagerChange()
+  }
+  const onAcceptManager = async () => {
+    await fn.acceptManager()
+  }
+  const onCancelManagerProposal = async () => {
+    await fn.cancelManagerProposal()
   }
 
   return (
     <SlnWarpper>
       <Btn onClick={onInitConfig} content="Init Config" />
-      <Btn onClick={onSetManager} content="Set Manager" />
+      <Btn onClick={onQueueManagerChange} content="Queue Manager Change" />
+      <Btn onClick={onAcceptManager} content="Accept Manager" />
+      <Btn onClick={onCancelManagerProposal} content="Cancel Manager Proposal" />
     </SlnWarpper>
   )
 }
 
-// set_instance_limit, set_addon_limit, request_admin, reject_admin,
+// queue_change (limits), request_admin, reject_admin,
 const SlnInfo = () => {
   const { state, fn } = useSlnInfo()
   const onSetInstanceLimit = async () => {
@@ -1,15 +1,16 @@
 'use client'
 
 import { getOpenClubProgram, getOpenClubProgramId } from '../../../anchor/src'
+import { BN } from '@coral-xyz/anchor'
 import { useConnection } from '@solana/wallet-adapter-react'
-import { Cluster, Keypair, PublicKey, Signer } from '@solana/web3.js'
+import { Cluster, PublicKey, Signer } from '@solana/web3.js'
 import { useMutation, useQuery } from '@tanstack/react-query'
 import { useMemo } from 'react'
 import { useCluster } from '../cluster/cluster-data-access'
 import { useAnchorProvider } from '../solana/solana-provider'
 import { useTransactionToast } from '../use-transaction-toast'
 import { toast } from 'sonner'
-import { ICreateInstance, IInitConfig, ISignerBool, ISignerNumber, ISignerWallet, Util } from './util'
+import { ICreateInstance, IInitConfig, ISigner, ISignerBool, ISignerNumber, ISignerWallet, Util } from './util'
 
 export const useSlnContext = () => {
   const { connection } = useConnection()
@@ -26,7 +27,7 @@ export const useSlnContext = () => {
   return { connection, program, programId, getProgramAccount, cluster, provider }
 }
 
-// config | info - init_config, set_manager
+// config | info - init_config, queue_change (manager), accept_manager, cancel_manager_proposal
 export const useSlnConfig = () => {
   const transactionToast = useTransactionToast()
   const _ctx = useSlnContext()
@@ -49,28 +50,49 @@ export const useSlnConfig = () => {
   // .initConfig('The Concrete Garden', 'A Timely, Inspiring, and Uplifting Story', 15, 10)
   const initConfig = useMutation({
     mutationKey: ['config_details', 'init', { cluster }],
-    mutationFn: ({ signer, name, title, instanceLimit, addonLimit }: IInitConfig) =>
+    // The caller keeps `club`: its public key locates the config for every later call (Util.getConfigAddress)
+    mutationFn: ({ signer, club, name, title, instanceLimit, addonLimit, timelockDelay }: IInitConfig) =>
       program.methods
-        .initConfig(name, title, instanceLimit, addonLimit)
-        .accounts({ manager: signer.publicKey })
-        .signers([signer])
+        .initConfig(name, title, instanceLimit, addonLimit, new BN(timelockDelay))
+        .accounts({ manager: signer.publicKey, club: club.publicKey })
+        .signers([signer, club])
         .rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [config, info]),
     onError: () => Util.onMutError(toast, 'Failed to Initialize Configuration'),
   })
 
-  const setManager = useMutation({
-    mutationKey: ['new_manager', 'update', { cluster }],
-    mutationFn: ({ signer, wallet }: ISignerWallet) =>
-      program.methods.setManager(wallet).accounts({ manager: signer.publicKey }).signers([signer]).rpc(),
+  const queueManagerChange = useMutation({
+    mutationKey: ['new_manager', 'queue', { cluster }],
+    mutationFn: ({ signer, config, wallet }: ISignerWallet) =>
+      program.methods
+        .queueChange({ manager: { newManager: wallet } })
+        .accounts({ signer: signer.publicKey, config })
+        .signers([signer])
+        .rpc(),
+    onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [config]),
+    onError: () => Util.onMutError(toast, 'Failed to Queue New Manager'),
+  })
+
+  const acceptManager = useMutation({
+    mutationKey: ['new_manager', 'accept', { cluster }],
+    mutationFn: ({ signer, config }: ISigner) =>
+      program.methods.acceptManager().accounts({ newManager: signer.publicKey, config }).signers([signer]).rpc(),
+    onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [config]),
+    onError: () => Util.onMutError(toast, 'Failed to Accept Manager Role'),
+  })
+
+  const cancelManagerProposal = useMutation({
+    mutationKey: ['new_manager', 'cancel', { cluster }],
+    mutationFn: ({ signer, config }: ISigner) =>
+      program.methods.cancelManagerProposal().accounts({ signer: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [config]),
-    onError: () => Util.onMutError(toast, 'Failed to Update New Manager'),
+    onError: () => Util.onMutError(toast, 'Failed to Cancel Manager Proposal'),
   })
 
-  return { ..._ctx, state: { config, info }, fn: { initConfig, setManager } }
+  return { ..._ctx, state: { config, info }, fn: { initConfig, queueManagerChange, acceptManager, cancelManagerProposal } }
 }
 
-// info - set_instance_limit, set_addon_limit, request_admin, reject_admin
+// info - queue_change (limits), request_admin, reject_admin
 export const useSlnInfo = () => {
   const transactionToast = useTransactionToast()
   const _ctx = useSlnContext()
@@ -86,32 +108,42 @@ export const useSlnInfo = () => {
 
   const setInstanceLimit = useMutation({
     mutationKey: ['instance', 'limit', { cluster }],
-    mutationFn: ({ signer, value }: ISignerNumber) =>
-      program.methods.setInstanceLimit(value).accounts({ signer: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, value }: ISignerNumber) =>
+      program.methods
+        .queueChange({ instanceLimit: { instanceLimit: value } })
+        .accounts({ signer: signer.publicKey, config })
+        .signers([signer])
+        .rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [info]),
     onError: () => Util.onMutError(toast, 'Unable to set Instance Limit'),
   })
 
   const setAddonLimit = useMutation({
     mutationKey: ['addon', 'limit', { cluster }],
-    mutationFn: ({ signer, value }: ISignerNumber) =>
-      program.methods.setAddonLimit(value).accounts({ signer: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, value }: ISignerNumber) =>
+      program.methods
+        .queueChange({
+          addonPackages: { packages: [{ slots: value, priceLamports: new BN(0), priceTokens: new BN(0), validityDays: 0 }] },
+        })
+        .accounts({ signer: signer.publicKey, config })
+        .signers([signer])
+        .rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [info]),
     onError: () => Util.onMutError(toast, 'Unable to set Addon Limit'),
   })
 
   const requestAdmin = useMutation({
     mutationKey: ['admin', 'request', { cluster }],
-    mutationFn: ({ signer }: { signer: Keypair }) =>
-      program.methods.requestAdmin().accounts({ signer: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, uri }: ISigner & { uri: string }) =>
+      program.methods.requestAdmin(uri).accounts({ requester: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [info]),
     onError: () => Util.onMutError(toast, 'Unable to request admin access'),
   })
 
   const rejectAdmin = useMutation({
     mutationKey: ['admin', 'reject', { cluster }],
-    mutationFn: ({ signer, wallet }: ISignerWallet) =>
-      program.methods.rejectAdmin(wallet).accounts({ manager: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, wallet }: ISignerWallet) =>
+      program.methods.rejectAdmin(wallet).accounts({ manager: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [info]),
     onError: () => Util.onMutError(toast, 'Unable to Reject admin access'),
   })
@@ -148,16 +180,16 @@ export const useSlnAdmin = () => {
   // *****************************  Methods  *****************************
   const approveAdmin = useMutation({
     mutationKey: ['admin', 'approve', { cluster }],
-    mutationFn: ({ signer, wallet }: ISignerWallet) =>
-      program.methods.approveAdmin(wallet).accounts({ manager: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, wallet }: ISignerWallet) =>
+      program.methods.approveAdmin(wallet).accounts({ manager: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [admin]),
     onError: () => Util.onMutError(toast, 'Unable to Approve admin access'),
   })
 
   const setAdminStatus = useMutation({
     mutationKey: ['admin_status', 'update', { cluster }],
-    mutationFn: ({ signer, value }: ISignerBool) =>
-      program.methods.setAdminStatus(value).accounts({ manager: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, value }: ISignerBool) =>
+      program.methods.setAdminStatus(value).accounts({ manager: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [admin]),
     onError: () => Util.onMutError(toast, 'Unable to request admin access'),
   })
@@ -180,16 +212,16 @@ export const useSlnCaptain = () => {
   // *****************************  State  *****************************
   const claimAddon = useMutation({
     mutationKey: ['whitelist', 'add', { cluster }],
-    mutationFn: ({ signer }: { signer: Keypair }) =>
-      program.methods.claimAddon().accounts({ payer: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, value }: ISignerNumber) =>
+      program.methods.claimAddon(value).accounts({ payer: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [captain]),
     onError: () => Util.onMutError(toast, 'Unable to aad whitelist'),
   })
 
   const claimCaptainship = useMutation({
     mutationKey: ['whitelist', 'add', { cluster }],
-    mutationFn: ({ signer }: { signer: Keypair }) =>
-      program.methods.claimCaptainship().accounts({ payer: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config }: ISigner) =>
+      program.methods.claimCaptainship().accounts({ payer: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [captain]),
     onError: () => Util.onMutError(toast, 'Unable to aad whitelist'),
   })
@@ -218,32 +250,36 @@ export const useSlnInstance = () => {
 
   const createInstance = useMutation({
     mutationKey: ['whitelist', 'add', { cluster }],
-    mutationFn: ({ creator, instanceType, name, days }: ICreateInstance) =>
-      program.methods.createInstance(instanceType, name, days).signers([creator]).rpc(),
+    mutationFn: ({ creator, config, instanceType, name, days, entryPrice, maxConsumers, opensAt }: ICreateInstance) =>
+      program.methods
+        .createInstance(instanceType, name, days, entryPrice, maxConsumers, opensAt ?? null)
+        .accounts({ creator: creator.publicKey, config })
+        .signers([creator])
+        .rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [instance]),
     onError: () => Util.onMutError(toast, 'Unable to aad whitelist'),
   })
 
   const grantPrivateInstance = useMutation({
     mutationKey: ['whitelist', 'add', { cluster }],
-    mutationFn: ({ signer, wallet }: ISignerWallet) =>
-      program.methods.grantPrivateInstance(wallet).accounts({ manager: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, wallet }: ISignerWallet) =>
+      program.methods.grantPrivateInstance(wallet).accounts({ signer: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [instance]),
     onError: () => Util.onMutError(toast, 'Unable to aad whitelist'),
   })
 
   const addWhitelist = useMutation({
     mutationKey: ['whitelist', 'add', { cluster }],
-    mutationFn: ({ signer, wallet }: ISignerWallet) =>
-      program.methods.addWhitelist(wallet).accounts({ manager: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config, wallet }: ISignerWallet) =>
+      program.methods.addWhitelist(wallet).accounts({ signer: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [instance]),
     onError: () => Util.onMutError(toast, 'Unable to aad whitelist'),
   })
 
   const claimInstance = useMutation({
     mutationKey: ['whitelist', 'add', { cluster }],
-    mutationFn: ({ signer }: { signer: Keypair }) =>
-      program.methods.claimInstance().accounts({ claimer: signer.publicKey }).signers([signer]).rpc(),
+    mutationFn: ({ signer, config }: ISigner) =>
+      program.methods.claimInstance([], null).accounts({ claimer: signer.publicKey, config }).signers([signer]).rpc(),
     onSuccess: async (signature) => Util.onMutSuccess(transactionToast, signature, [instance]),
     onError: () => Util.onMutError(toast, 'Unable to aad whitelist'),
   })
@@ -1,3 +1,4 @@
+import { BN } from '@coral-xyz/anchor'
 import { Keypair, PublicKey } from '@solana/web3.js'
 
 interface Refetchable {
@@ -11,27 +12,40 @@ const onMutSuccess = async (toast: any, signature: any, items: Refetchable[]) =>
 
 const onMutError = async (toast: any, msg: string) => toast?.error('Failed to Initialize Configuration')
 
-export type ISignerWallet = { signer: Keypair; wallet: PublicKey }
-export type ISignerNumber = { signer: Keypair; value: number }
-export type ISignerBool = { signer: Keypair; value: boolean }
+// `config` is the club's Config PDA; see getConfigAddress
+export type ISigner = { signer: Keypair; config: PublicKey }
+export type ISignerWallet = ISigner & { wallet: PublicKey }
+export type ISignerNumber = ISigner & { value: number }
+export type ISignerBool = ISigner & { value: boolean }
 
 export type IInitConfig = {
   signer: Keypair
+  club: Keypair
   name: string
   title: string
   instanceLimit: number
   addonLimit: number
+  timelockDelay: number
 }
 export type InstanceType = { private: number } | { whitelisted: number } | { portfolio: number } | { public: number }
 
 export type ICreateInstance = {
   creator: Keypair
+  config: PublicKey
   instanceType: InstanceType
   name: string
   days: number
+  entryPrice: BN
+  maxConsumers: number
+  opensAt?: BN
 }
 
+// Config PDA of the club created with `club` in init_config
+const getConfigAddress = (programId: PublicKey, club: PublicKey) =>
+  PublicKey.findProgramAddressSync([Buffer.from('config'), club.toBuffer()], programId)[0]
+
 export const Util = {
   onMutSuccess,
   onMutError,
+  getConfigAddress,
 }